@@ -0,0 +1,161 @@
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+
+use bsky::Bsky;
+use http_body_util::Full;
+use hyper::{
+  body::{Bytes, Incoming},
+  header::CONTENT_TYPE,
+  server::conn::http1,
+  service::service_fn,
+  Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use repositories::{watched_user, Database};
+use serde_json::{json, Map, Value};
+use services::{
+  health::{self, JobState},
+  jobs::user_watcher,
+};
+use tokio::{net::TcpListener, time::timeout};
+use tracing::{event, Level};
+
+/// How long the database may take to answer the readiness check.
+static DATABASE_PING_TIMEOUT: u64 = 5; // 5 Seconds
+
+/// Serves the bot's metrics over HTTP, at `/metrics`, its health checks, at `/healthz`
+/// and `/readyz`, and the state of its polls, at `/debug/polls`, until the app exits.
+/// Only started when `HTTP_SERVER_ADDR` is set.
+pub async fn serve(addr: SocketAddr) {
+  let listener = match TcpListener::bind(addr).await {
+    Ok(listener) => listener,
+    Err(e) => {
+      event!(
+        Level::ERROR,
+        "Failed to start the HTTP server on {addr}: {e}"
+      );
+      return;
+    }
+  };
+  event!(Level::INFO, "HTTP server listening on {addr}.");
+
+  loop {
+    let tcp = match listener.accept().await {
+      Ok((tcp, _)) => tcp,
+      Err(e) => {
+        event!(
+          Level::WARN,
+          "(Notice) Failed to accept HTTP connection: {e}"
+        );
+        continue;
+      }
+    };
+    tokio::spawn(async move {
+      let connection =
+        http1::Builder::new().serve_connection(TokioIo::new(tcp), service_fn(handle));
+      let Err(e) = connection.await else {
+        return;
+      };
+      event!(Level::DEBUG, "HTTP connection failed: {e}");
+    });
+  }
+}
+
+/// Routes a single request.
+async fn handle(request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+  let response = match (request.method(), request.uri().path()) {
+    (&Method::GET, "/metrics") => {
+      let (watched_users, watchers) = watched_user::count().await;
+      metrics::WATCHED_USERS.set(i64::try_from(watched_users).unwrap_or(i64::MAX));
+      metrics::WATCHERS.set(i64::try_from(watchers).unwrap_or(i64::MAX));
+      respond(
+        StatusCode::OK,
+        "text/plain; version=0.0.4",
+        metrics::encode(),
+      )
+    }
+    (&Method::GET, "/healthz") => {
+      let (healthy, jobs) = jobs();
+      let report = json!({ "healthy": healthy, "jobs": jobs });
+      respond_with_report(healthy, &report)
+    }
+    (&Method::GET, "/readyz") => {
+      let (healthy, jobs) = jobs();
+      let database = timeout(Duration::from_secs(DATABASE_PING_TIMEOUT), Database::ping())
+        .await
+        .unwrap_or(false);
+      let bsky_agent = Bsky::is_agent_valid();
+      let ready = healthy && database && bsky_agent;
+      let report = json!({
+        "ready": ready,
+        "jobs": jobs,
+        "database": database,
+        "bsky_agent": bsky_agent,
+      });
+      respond_with_report(ready, &report)
+    }
+    (&Method::GET, "/debug/polls") => {
+      let report = polls().await;
+      respond(StatusCode::OK, "application/json", report.to_string())
+    }
+    _ => respond(
+      StatusCode::NOT_FOUND,
+      "text/plain",
+      "Not Found\n".to_string(),
+    ),
+  };
+  Ok(response)
+}
+
+/// Reports the state of every job started so far, along with whether none of them stopped.
+fn jobs() -> (bool, Value) {
+  let jobs = health::jobs();
+  let healthy = jobs
+    .iter()
+    .all(|(_, status)| status.state == JobState::Running);
+  let report = jobs
+    .into_iter()
+    .map(|(job, status)| {
+      let report = json!({ "state": status.state.name(), "since": status.since.to_rfc3339() });
+      (job.name().to_string(), report)
+    })
+    .collect::<Map<_, _>>();
+  (healthy, Value::Object(report))
+}
+
+/// Reports the state of the polling of every watched user, in `polling` mode.
+async fn polls() -> Value {
+  let report = user_watcher::inspect()
+    .await
+    .into_iter()
+    .map(|(watched_did, status)| {
+      let report = json!({
+        "next_poll_at": status.next_poll_at.map(|at| at.to_rfc3339()),
+        "interval_secs": status.interval.num_seconds(),
+        "last_success": status.last_success.map(|at| at.to_rfc3339()),
+        "failures_in_a_row": status.failures_in_a_row,
+        "rearms_in_a_row": status.rearms_in_a_row,
+        "suspended_since": status.suspended_since.map(|at| at.to_rfc3339()),
+      });
+      (watched_did.to_string(), report)
+    })
+    .collect::<Map<_, _>>();
+  Value::Object(report)
+}
+
+fn respond_with_report(ok: bool, report: &Value) -> Response<Full<Bytes>> {
+  let status = if ok {
+    StatusCode::OK
+  } else {
+    StatusCode::SERVICE_UNAVAILABLE
+  };
+  respond(status, "application/json", report.to_string())
+}
+
+fn respond(status: StatusCode, content_type: &str, body: String) -> Response<Full<Bytes>> {
+  #[expect(clippy::unwrap_used)] // Status and headers are always valid
+  Response::builder()
+    .status(status)
+    .header(CONTENT_TYPE, content_type)
+    .body(Full::new(Bytes::from(body)))
+    .unwrap()
+}
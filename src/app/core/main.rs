@@ -1,9 +1,19 @@
+mod http_server;
 mod on_shutdown;
 
-use environment::{TURN_OFF_WATCHED_NOTIFS, WORKSPACE_DIR};
+use std::net::SocketAddr;
+
+use chrono::Utc;
+use environment::{
+  owned_var_try, IngestionMode, INGESTION_MODE, TURN_OFF_HANDLE_CHANGE_NOTIFS,
+  TURN_OFF_WATCHED_NOTIFS, WORKSPACE_DIR,
+};
 use on_shutdown::with_graceful_shutdown;
-use repositories::Database;
-use services::jobs;
+use repositories::{Backend, Database};
+use services::{
+  health::Job,
+  jobs::{self, supervisor::supervise},
+};
 use tracing::{event, Level};
 
 #[cfg(unix)]
@@ -13,7 +23,12 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 /// Main entry point for the application.
 ///
 /// This function initializes the logging system, runs the database migrations, and starts the
-/// command listener and issuer. It also starts watching users for new posts.
+/// command listener and issuer. It also starts watching users for new posts, either through
+/// Jetstream or by polling, depending on `INGESTION_MODE`, recovers the posts made while
+/// the bot was down, and sends digests to the watchers that prefer them. The watchlist is
+/// reloaded from the database from time to time, in case it's shared. If `HTTP_SERVER_ADDR`
+/// is set, the bot's metrics and health checks are also served over HTTP.
+/// Jobs that stop are restarted, and if any of them keeps stopping, the bot shuts down.
 #[tokio::main]
 async fn main() {
   dotenv::from_filename(WORKSPACE_DIR.join(".env")).ok();
@@ -22,33 +37,69 @@ async fn main() {
 
   // Database auto migration
   event!(Level::INFO, "Running DB migrations...");
-  sqlx::migrate!()
-    .run(Database::get_pool().await)
+  let migrator = match Database::backend().await {
+    Backend::Sqlite => sqlx::migrate!("./migrations/sqlite"),
+    #[cfg(feature = "postgres")]
+    Backend::Postgres => sqlx::migrate!("./migrations/postgres"),
+  };
+  Database::migrate(&migrator)
     .await
     .unwrap_or_else(|e| panic!("Failed to migrate DB! Error: {e}"));
 
   event!(Level::INFO, "Application starting!");
 
   if *TURN_OFF_WATCHED_NOTIFS {
-    event!(Level::INFO, "Bot will not notify users that they are being watched. Feature disabled in environment.");
+    event!(
+      Level::INFO,
+      "Bot will not notify users that they are being watched. Feature disabled in environment."
+    );
   } else {
-    event!(Level::INFO, "Bot is set to notify users that they are being watched.");
+    event!(
+      Level::INFO,
+      "Bot is set to notify users that they are being watched."
+    );
+  }
+  if *TURN_OFF_HANDLE_CHANGE_NOTIFS {
+    event!(
+      Level::INFO,
+      "Bot will not tell watchers about handle changes. Feature disabled in environment."
+    );
+  }
+
+  if let Ok(addr) = owned_var_try::<SocketAddr>("HTTP_SERVER_ADDR") {
+    tokio::spawn(http_server::serve(addr));
   }
 
-  #[expect(clippy::redundant_pub_crate)] // Select macro propagates this
-  let commands_fut = async {
-    let listener = jobs::command_listener::begin();
-    let issuer = jobs::command_issuer::begin();
+  // Posts up until now are backfilled, anything after is left for the ingestion job
+  let started_at = Utc::now();
+  tokio::spawn(jobs::backfill::begin(started_at));
+  let ingestion_fut = async {
+    match *INGESTION_MODE {
+      IngestionMode::Jetstream => {
+        event!(Level::INFO, "Watching users' posts through Jetstream.");
+        supervise(Job::JetstreamListener, || {
+          jobs::jetstream_listener::begin(started_at)
+        })
+        .await;
+      }
+      IngestionMode::Polling => {
+        event!(Level::INFO, "Watching users' posts by polling their feeds.");
+        supervise(Job::UserWatcher, || jobs::user_watcher::begin(started_at)).await;
+      }
+    }
+  };
 
+  // Every job is restarted when it stops, so any of these returning means one gave up
+  let jobs_fut = async {
     tokio::select! {
-      () = listener => {},
-      () = issuer => {},
+      () = supervise(Job::CommandListener, jobs::command_listener::begin) => {},
+      () = supervise(Job::CommandIssuer, jobs::command_issuer::begin) => {},
+      () = supervise(Job::DigestSender, jobs::digest_sender::begin) => {},
+      () = supervise(Job::NotificationSender, jobs::notification_sender::begin) => {},
+      () = supervise(Job::WatchlistReloader, jobs::watchlist_reloader::begin) => {},
+      () = ingestion_fut => {},
     }
-
-    event!(Level::WARN, "Commands are no longer being processed...");
   };
-  tokio::spawn(commands_fut);
-  tokio::spawn(jobs::user_watcher::begin());
 
-  with_graceful_shutdown(discord_worker).await;
+  with_graceful_shutdown(discord_worker, jobs_fut).await;
 }
@@ -1,3 +1,5 @@
+use std::{future::Future, process};
+
 use repositories::Database;
 use tokio::signal;
 use tracing::{event, Level};
@@ -19,10 +21,20 @@ async fn before_shutdown(discord_worker: Option<BackgroundWorker>) {
   }
 }
 
-/// Routine for gracefully handling the bot shutdown.
-pub async fn with_graceful_shutdown(discord_worker: Option<BackgroundWorker>) {
-  shutdown_signal().await;
+/// Routine for gracefully handling the bot shutdown, either when it's asked to or when `jobs`
+/// give up, in which case the bot exits with an error code afterwards.
+pub async fn with_graceful_shutdown(
+  discord_worker: Option<BackgroundWorker>,
+  jobs: impl Future<Output = ()>,
+) {
+  let gave_up = tokio::select! {
+    () = shutdown_signal() => false,
+    () = jobs => true,
+  };
   before_shutdown(discord_worker).await;
+  if gave_up {
+    process::exit(1);
+  }
 }
 
 /// Installs signal handlers for SIGTERM/SIGINT.
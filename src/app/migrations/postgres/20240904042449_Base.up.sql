@@ -0,0 +1,5 @@
+CREATE TABLE "WatchedUser" (
+    did TEXT NOT NULL,
+    watchers TEXT NOT NULL,
+    PRIMARY KEY (did)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE "WatchedUser" ADD COLUMN last_post_at TIMESTAMPTZ;
+UPDATE "WatchedUser" SET last_post_at = CURRENT_TIMESTAMP;
@@ -0,0 +1,5 @@
+CREATE TABLE "WatcherSettings" (
+    did TEXT NOT NULL,
+    group_posts BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (did)
+);
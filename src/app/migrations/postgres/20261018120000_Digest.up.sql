@@ -0,0 +1,12 @@
+ALTER TABLE "WatcherSettings" ADD COLUMN digest TEXT NOT NULL DEFAULT 'off';
+ALTER TABLE "WatcherSettings" ADD COLUMN digest_sent_at TIMESTAMPTZ;
+
+CREATE TABLE "QueuedNotification" (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    watcher_did TEXT NOT NULL,
+    watched_did TEXT NOT NULL,
+    post_uri TEXT NOT NULL,
+    is_reply BOOLEAN NOT NULL,
+    queued_at TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX "QueuedNotification_watcher_did" ON "QueuedNotification" (watcher_did);
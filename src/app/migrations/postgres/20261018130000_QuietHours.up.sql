@@ -0,0 +1,3 @@
+ALTER TABLE "WatcherSettings" ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
+ALTER TABLE "WatcherSettings" ADD COLUMN quiet_start TIME;
+ALTER TABLE "WatcherSettings" ADD COLUMN quiet_end TIME;
@@ -0,0 +1,8 @@
+ALTER TABLE "WatchedUser" ADD COLUMN watchers TEXT NOT NULL DEFAULT '[]';
+
+UPDATE "WatchedUser" SET watchers = COALESCE((
+    SELECT json_agg(json_build_object('0', watcher_did, '1', watch_replies))::TEXT
+    FROM "Watch" WHERE "Watch".watched_did = "WatchedUser".did
+), '[]');
+
+DROP TABLE "Watch";
@@ -0,0 +1,20 @@
+CREATE TABLE "Watch" (
+    watched_did TEXT NOT NULL,
+    watcher_did TEXT NOT NULL,
+    watch_replies BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (watched_did, watcher_did),
+    FOREIGN KEY (watched_did) REFERENCES "WatchedUser" (did) ON DELETE CASCADE
+);
+CREATE INDEX "Watch_watcher_did" ON "Watch" (watcher_did);
+
+-- Watchers used to be stored as a JSON set of `{"0": watcher_did, "1": watch_replies}`
+INSERT INTO "Watch" (watched_did, watcher_did, watch_replies, created_at)
+SELECT
+    "WatchedUser".did,
+    watcher.value ->> '0',
+    (watcher.value ->> '1')::BOOLEAN,
+    CURRENT_TIMESTAMP
+FROM "WatchedUser", jsonb_array_elements("WatchedUser".watchers::JSONB) AS watcher;
+
+ALTER TABLE "WatchedUser" DROP COLUMN watchers;
@@ -0,0 +1,7 @@
+CREATE TABLE "PostActivity" (
+    watched_did TEXT NOT NULL,
+    post_uri TEXT NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (watched_did, post_uri),
+    FOREIGN KEY (watched_did) REFERENCES "WatchedUser" (did) ON DELETE CASCADE
+);
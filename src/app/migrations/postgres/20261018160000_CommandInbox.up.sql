@@ -0,0 +1,13 @@
+CREATE TABLE "CommandInbox" (
+    message_id TEXT PRIMARY KEY NOT NULL,
+    convo_id TEXT NOT NULL,
+    sender_did TEXT NOT NULL,
+    text TEXT NOT NULL,
+    facets TEXT,
+    sent_at TIMESTAMPTZ NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts BIGINT NOT NULL DEFAULT 0,
+    received_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ
+);
+CREATE INDEX "CommandInbox_status" ON "CommandInbox" (status);
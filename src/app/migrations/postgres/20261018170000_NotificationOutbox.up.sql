@@ -0,0 +1,18 @@
+CREATE TABLE "NotificationOutbox" (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    watcher_did TEXT NOT NULL,
+    watched_did TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    post_uri TEXT,
+    post_cid TEXT,
+    post_text TEXT,
+    is_reply BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts BIGINT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ,
+    UNIQUE (watcher_did, post_uri)
+);
+CREATE INDEX "NotificationOutbox_status_next_attempt_at" ON "NotificationOutbox" (status, next_attempt_at);
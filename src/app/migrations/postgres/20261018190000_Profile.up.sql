@@ -0,0 +1,4 @@
+ALTER TABLE "WatchedUser" ADD COLUMN handle TEXT;
+ALTER TABLE "WatchedUser" ADD COLUMN display_name TEXT;
+ALTER TABLE "WatchedUser" ADD COLUMN avatar TEXT;
+ALTER TABLE "NotificationOutbox" ADD COLUMN previous_handle TEXT;
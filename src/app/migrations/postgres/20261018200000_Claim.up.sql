@@ -0,0 +1 @@
+ALTER TABLE "CommandInbox" ADD COLUMN claimed_until TIMESTAMPTZ;
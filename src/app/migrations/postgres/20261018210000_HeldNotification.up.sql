@@ -0,0 +1,8 @@
+ALTER TABLE "NotificationOutbox" ADD COLUMN in_digest BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO "NotificationOutbox"
+    (watcher_did, watched_did, kind, post_uri, is_reply, status, in_digest, next_attempt_at, created_at)
+SELECT watcher_did, watched_did, 'post', post_uri, is_reply, 'held', TRUE, queued_at, queued_at
+FROM "QueuedNotification"
+ON CONFLICT (watcher_did, post_uri) DO NOTHING;
+DROP TABLE "QueuedNotification";
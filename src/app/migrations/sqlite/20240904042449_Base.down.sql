@@ -0,0 +1 @@
+DROP TABLE "WatchedUser";
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE "WatchedUser" DROP COLUMN last_post_at;
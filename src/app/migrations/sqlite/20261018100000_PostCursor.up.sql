@@ -0,0 +1,2 @@
+ALTER TABLE "WatchedUser" ADD COLUMN last_post_at DATETIME;
+UPDATE "WatchedUser" SET last_post_at = CURRENT_TIMESTAMP;
@@ -0,0 +1,3 @@
+DROP TABLE "QueuedNotification";
+ALTER TABLE "WatcherSettings" DROP COLUMN digest_sent_at;
+ALTER TABLE "WatcherSettings" DROP COLUMN digest;
@@ -0,0 +1,12 @@
+ALTER TABLE "WatcherSettings" ADD COLUMN digest TEXT NOT NULL DEFAULT 'off';
+ALTER TABLE "WatcherSettings" ADD COLUMN digest_sent_at DATETIME;
+
+CREATE TABLE "QueuedNotification" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    watcher_did CHAR(32) NOT NULL,
+    watched_did CHAR(32) NOT NULL,
+    post_uri TEXT NOT NULL,
+    is_reply BOOLEAN NOT NULL,
+    queued_at DATETIME NOT NULL
+);
+CREATE INDEX "QueuedNotification_watcher_did" ON "QueuedNotification" (watcher_did);
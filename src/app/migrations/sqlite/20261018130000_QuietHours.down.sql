@@ -0,0 +1,3 @@
+ALTER TABLE "WatcherSettings" DROP COLUMN quiet_end;
+ALTER TABLE "WatcherSettings" DROP COLUMN quiet_start;
+ALTER TABLE "WatcherSettings" DROP COLUMN timezone;
@@ -0,0 +1,11 @@
+ALTER TABLE "WatchedUser" ADD COLUMN watchers TEXT NOT NULL DEFAULT '[]';
+
+UPDATE "WatchedUser" SET watchers = (
+    SELECT json_group_array(json_object(
+        '0', watcher_did,
+        '1', json(CASE WHEN watch_replies THEN 'true' ELSE 'false' END)
+    ))
+    FROM "Watch" WHERE "Watch".watched_did = "WatchedUser".did
+);
+
+DROP TABLE "Watch";
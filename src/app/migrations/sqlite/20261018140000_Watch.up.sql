@@ -0,0 +1,20 @@
+CREATE TABLE "Watch" (
+    watched_did CHAR(32) NOT NULL,
+    watcher_did CHAR(32) NOT NULL,
+    watch_replies BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (watched_did, watcher_did),
+    FOREIGN KEY (watched_did) REFERENCES "WatchedUser" (did) ON DELETE CASCADE
+);
+CREATE INDEX "Watch_watcher_did" ON "Watch" (watcher_did);
+
+-- Watchers used to be stored as a JSON set of `{"0": watcher_did, "1": watch_replies}`
+INSERT INTO "Watch" (watched_did, watcher_did, watch_replies, created_at)
+SELECT
+    "WatchedUser".did,
+    json_extract(watcher.value, '$."0"'),
+    json_extract(watcher.value, '$."1"'),
+    CURRENT_TIMESTAMP
+FROM "WatchedUser", json_each("WatchedUser".watchers) AS watcher;
+
+ALTER TABLE "WatchedUser" DROP COLUMN watchers;
@@ -0,0 +1,13 @@
+CREATE TABLE "CommandInbox" (
+    message_id TEXT PRIMARY KEY NOT NULL,
+    convo_id TEXT NOT NULL,
+    sender_did CHAR(32) NOT NULL,
+    text TEXT NOT NULL,
+    facets TEXT,
+    sent_at DATETIME NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    received_at DATETIME NOT NULL,
+    finished_at DATETIME
+);
+CREATE INDEX "CommandInbox_status" ON "CommandInbox" (status);
@@ -0,0 +1,18 @@
+CREATE TABLE "NotificationOutbox" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    watcher_did CHAR(32) NOT NULL,
+    watched_did CHAR(32) NOT NULL,
+    kind TEXT NOT NULL,
+    post_uri TEXT,
+    post_cid TEXT,
+    post_text TEXT,
+    is_reply BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at DATETIME NOT NULL,
+    last_error TEXT,
+    created_at DATETIME NOT NULL,
+    finished_at DATETIME,
+    UNIQUE (watcher_did, post_uri)
+);
+CREATE INDEX "NotificationOutbox_status_next_attempt_at" ON "NotificationOutbox" (status, next_attempt_at);
@@ -0,0 +1 @@
+ALTER TABLE "WatchedUser" DROP COLUMN suspended_at;
@@ -0,0 +1 @@
+ALTER TABLE "WatchedUser" ADD COLUMN suspended_at DATETIME;
@@ -0,0 +1,4 @@
+ALTER TABLE "NotificationOutbox" DROP COLUMN previous_handle;
+ALTER TABLE "WatchedUser" DROP COLUMN avatar;
+ALTER TABLE "WatchedUser" DROP COLUMN display_name;
+ALTER TABLE "WatchedUser" DROP COLUMN handle;
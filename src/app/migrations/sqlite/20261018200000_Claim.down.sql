@@ -0,0 +1 @@
+ALTER TABLE "CommandInbox" DROP COLUMN claimed_until;
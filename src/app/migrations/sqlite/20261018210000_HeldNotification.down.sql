@@ -0,0 +1,15 @@
+CREATE TABLE "QueuedNotification" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    watcher_did CHAR(32) NOT NULL,
+    watched_did CHAR(32) NOT NULL,
+    post_uri TEXT NOT NULL,
+    is_reply BOOLEAN NOT NULL,
+    queued_at DATETIME NOT NULL
+);
+CREATE INDEX "QueuedNotification_watcher_did" ON "QueuedNotification" (watcher_did);
+
+INSERT INTO "QueuedNotification" (watcher_did, watched_did, post_uri, is_reply, queued_at)
+SELECT watcher_did, watched_did, post_uri, is_reply, created_at
+FROM "NotificationOutbox" WHERE status = 'held';
+DELETE FROM "NotificationOutbox" WHERE status = 'held';
+ALTER TABLE "NotificationOutbox" DROP COLUMN in_digest;
@@ -0,0 +1,9 @@
+ALTER TABLE "NotificationOutbox" ADD COLUMN in_digest BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Sqlite requires a WHERE clause for an upsert from a SELECT to be parsed
+INSERT INTO "NotificationOutbox"
+    (watcher_did, watched_did, kind, post_uri, is_reply, status, in_digest, next_attempt_at, created_at)
+SELECT watcher_did, watched_did, 'post', post_uri, is_reply, 'held', TRUE, queued_at, queued_at
+FROM "QueuedNotification" WHERE TRUE
+ON CONFLICT (watcher_did, post_uri) DO NOTHING;
+DROP TABLE "QueuedNotification";
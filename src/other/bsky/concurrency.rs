@@ -0,0 +1,100 @@
+use environment::owned_var_or;
+use lazy_static::lazy_static;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::{event, Level};
+
+/// Whether a request only reads data, or also changes something, e.g. sends a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+  Read,
+  Write,
+}
+impl Access {
+  fn limiter(self) -> &'static Semaphore {
+    match self {
+      Self::Read => &READS,
+      Self::Write => &WRITES,
+    }
+  }
+
+  /// The label of the metrics about this kind of request.
+  const fn label(self) -> &'static str {
+    match self {
+      Self::Read => "read",
+      Self::Write => "write",
+    }
+  }
+}
+
+lazy_static! {
+  /// Caps how many requests of each kind may be in flight at once, across all of the bot's jobs.
+  static ref READS: Semaphore =
+    Semaphore::new(owned_var_or("BSKY_MAX_CONCURRENT_READS", 20).max(1));
+  static ref WRITES: Semaphore =
+    Semaphore::new(owned_var_or("BSKY_MAX_CONCURRENT_WRITES", 5).max(1));
+}
+
+/// Holds a request's turn until dropped, counting it as in flight meanwhile.
+pub struct Permit {
+  _permit: SemaphorePermit<'static>,
+  access: Access,
+}
+impl Permit {
+  fn new(permit: SemaphorePermit<'static>, access: Access) -> Self {
+    metrics::BSKY_IN_FLIGHT
+      .with_label_values(&[access.label()])
+      .inc();
+    Self {
+      _permit: permit,
+      access,
+    }
+  }
+}
+impl Drop for Permit {
+  fn drop(&mut self) {
+    metrics::BSKY_IN_FLIGHT
+      .with_label_values(&[self.access.label()])
+      .dec();
+  }
+}
+
+/// Waits for a request's turn, returning the permit that holds it until dropped.
+pub async fn acquire(access: Access) -> Permit {
+  let limiter = access.limiter();
+  if let Ok(permit) = limiter.try_acquire() {
+    return Permit::new(permit, access);
+  }
+
+  let _waiting = Waiting::new(access);
+  metrics::BSKY_QUEUED
+    .with_label_values(&[access.label()])
+    .inc();
+  event!(
+    Level::DEBUG,
+    "Too many {access:?} requests in flight, {} waiting for their turn.",
+    metrics::BSKY_QUEUE_WAITING
+      .with_label_values(&[access.label()])
+      .get()
+  );
+  #[expect(clippy::unwrap_used)] // The semaphore is never closed
+  let permit = limiter.acquire().await.unwrap();
+  Permit::new(permit, access)
+}
+
+/// Counts a request as waiting for its turn until dropped, even if it stopped waiting early.
+struct Waiting(Access);
+impl Waiting {
+  fn new(access: Access) -> Self {
+    metrics::BSKY_QUEUE_WAITING
+      .with_label_values(&[access.label()])
+      .inc();
+    Self(access)
+  }
+}
+impl Drop for Waiting {
+  fn drop(&mut self) {
+    metrics::BSKY_QUEUE_WAITING
+      .with_label_values(&[self.0.label()])
+      .dec();
+  }
+}
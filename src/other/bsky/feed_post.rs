@@ -0,0 +1,67 @@
+use atrium_api::{
+  app::bsky::feed::{
+    defs::{FeedViewPostData, PostViewData, ReplyRefParentRefs},
+    post,
+  },
+  types::{string::Cid, TryFromUnknown},
+};
+use chrono::{DateTime, Utc};
+use tracing::{event, Level};
+use utils::{handle_union, is_reply};
+
+/// A post found in a user's author feed.
+#[derive(Debug, Clone)]
+pub struct FeedPost {
+  pub uri: String,
+  pub cid: Cid,
+  pub text: String,
+  pub indexed_at: DateTime<Utc>,
+  /// Whether the post is a reply to someone else. Replies within the author's own threads
+  /// are considered regular posts.
+  pub is_reply: bool,
+}
+impl FeedPost {
+  /// Converts an item of an author feed into a post.
+  ///
+  /// # Returns
+  /// `None` if the item is a repost, given that it isn't a post from the feed's author.
+  pub(crate) fn from_feed_item(item: FeedViewPostData) -> Option<Self> {
+    if item.reason.is_some() {
+      return None;
+    }
+
+    let PostViewData {
+      uri,
+      cid,
+      record,
+      indexed_at,
+      author,
+      ..
+    } = item.post.data;
+    let text = post::Record::try_from_unknown(record).map_or_else(
+      |e| {
+        event!(Level::WARN, "(Notice) Received invalid post record: {e}");
+        String::new()
+      },
+      |record| record.data.text,
+    );
+    let author_did = author.did.as_str();
+    let is_reply = item
+      .reply
+      .is_some_and(|reply| match handle_union(reply.data.parent) {
+        Some(ReplyRefParentRefs::PostView(parent)) => is_reply(author_did, &parent.uri),
+        Some(ReplyRefParentRefs::NotFoundPost(parent)) => is_reply(author_did, &parent.uri),
+        Some(ReplyRefParentRefs::BlockedPost(parent)) => is_reply(author_did, &parent.uri),
+        // Parents of an unknown type can't be told apart from someone else's post
+        None => true,
+      });
+
+    Some(Self {
+      uri,
+      cid,
+      text,
+      indexed_at: indexed_at.as_ref().with_timezone(&Utc),
+      is_reply,
+    })
+  }
+}
@@ -12,7 +12,7 @@ use thiserror::Error as ThisError;
 use utils::handle_union;
 use xrpc::error::Error as XrpcError;
 
-use crate::BskyReq;
+use crate::{rate_limit::Endpoint, BskyReq};
 
 #[derive(ThisError, Debug)]
 pub enum Error {}
@@ -71,6 +71,7 @@ impl BskyReq for Request {
   type ReqOutput = get_messages::OutputData;
   type ReqError = get_messages::Error;
   type HandledError = Error;
+  const ENDPOINT: Endpoint = Endpoint::Chat;
 
   fn get_params(self) -> Self::ReqParams {
     get_messages::Parameters {
@@ -0,0 +1,146 @@
+use super::Bsky;
+use atrium_api::{
+  types::{string::AtIdentifier, Object},
+  xrpc::error::{Error as XrpcError, ErrorResponseBody},
+};
+use bsky_sdk::api::app::bsky::feed::get_author_feed;
+use chrono::{DateTime, Utc};
+use ipld_core::ipld::Ipld;
+use thiserror::Error as ThisError;
+use tracing::{event, Level};
+
+use crate::{BskyReq, FeedPost};
+
+#[derive(ThisError, Debug)]
+pub enum Error {
+  #[error("User has opted out of being watched")]
+  UserOptedOut,
+  #[error("User's account is deactivated")]
+  AccountDeactivated,
+  #[error("User's account was taken down")]
+  AccountTakedown,
+  #[error("User's account was not found")]
+  AccountNotFound,
+}
+
+static PAGE_SIZE: u8 = 25;
+
+/// Method to get all posts and replies of a user made within a time window, paginating
+/// through their author feed until the start of the window is crossed.
+///
+/// # Errors
+///
+/// Any unhandled request errors are passed up to the caller.
+///
+/// # Returns
+///
+/// The posts indexed after `since` and up until `until` (if any), from oldest to newest.
+pub async fn act(
+  actor: AtIdentifier,
+  since: DateTime<Utc>,
+  until: Option<DateTime<Utc>>,
+) -> Result<Vec<FeedPost>, super::Error<Error>> {
+  let until = until.unwrap_or(DateTime::<Utc>::MAX_UTC);
+  let mut curr_cursor = None;
+  let mut posts = Vec::new();
+  loop {
+    let get_author_feed::OutputData { cursor, feed } = Request {
+      actor: actor.clone(),
+      curr_cursor,
+    }
+    .act()
+    .await?;
+    if feed.is_empty() {
+      break;
+    }
+
+    posts.extend(
+      feed
+        .into_iter()
+        .filter_map(|item| FeedPost::from_feed_item(item.data))
+        .filter(|post| post.indexed_at > since && post.indexed_at <= until),
+    );
+
+    let Some(cursor) = cursor else {
+      break;
+    };
+    let crossed_since = cursor.parse::<DateTime<Utc>>().map_or_else(
+      |_| {
+        event!(
+          Level::WARN,
+          "(Notice) Received invalid timestamp for cursor: {:?}",
+          cursor
+        );
+        true
+      },
+      |cursor_time| cursor_time <= since,
+    );
+    if crossed_since {
+      break;
+    }
+    curr_cursor = Some(cursor);
+  }
+
+  posts.reverse();
+  Ok(posts)
+}
+
+struct Request {
+  actor: AtIdentifier,
+  curr_cursor: Option<String>,
+}
+impl BskyReq for Request {
+  type ReqParams = get_author_feed::Parameters;
+  type ReqOutput = get_author_feed::OutputData;
+  type ReqError = get_author_feed::Error;
+  type HandledError = Error;
+
+  fn get_params(self) -> Self::ReqParams {
+    Self::ReqParams {
+      data: get_author_feed::ParametersData {
+        actor: self.actor,
+        cursor: self.curr_cursor,
+        filter: Some("posts_with_replies".to_string()),
+        #[expect(clippy::unwrap_used)] // Safe because it's a constant
+        limit: Some(PAGE_SIZE.try_into().unwrap()),
+      },
+      extra_data: Ipld::Null,
+    }
+  }
+
+  async fn request(
+    params: Self::ReqParams,
+  ) -> Result<Object<Self::ReqOutput>, XrpcError<Self::ReqError>> {
+    Bsky::get_agent()
+      .await
+      .api
+      .app
+      .bsky
+      .feed
+      .get_author_feed(params)
+      .await
+  }
+
+  fn handle_xrpc_custom_error(err: Self::ReqError) -> Option<super::Error<Error>> {
+    match err {
+      Self::ReqError::BlockedByActor(_) | Self::ReqError::BlockedActor(_) => {
+        event!(Level::INFO, "User has opted out of being watched.");
+        Some(super::Error::Other(Error::UserOptedOut))
+      }
+    }
+  }
+
+  fn handle_xrpc_undefined_error(err: &ErrorResponseBody) -> Option<super::Error<Error>> {
+    let error = match err.error.as_deref()? {
+      "AccountDeactivated" => Error::AccountDeactivated,
+      "AccountTakedown" => Error::AccountTakedown,
+      // Deleted accounts, or ones that never existed
+      "InvalidRequest" if err.message.as_deref() == Some("Profile not found") => {
+        Error::AccountNotFound
+      }
+      _ => return None,
+    };
+    event!(Level::INFO, "User's account is unavailable: {error}.");
+    Some(super::Error::Other(error))
+  }
+}
@@ -13,6 +13,9 @@ use crate::BskyReq;
 #[derive(ThisError, Debug)]
 pub enum Error {}
 
+/// The maximum number of actors that can be fetched at once.
+pub const MAX_ACTORS: usize = 25;
+
 /// Gets a batch of profiles from the Bsky API.
 ///
 /// # Errors
@@ -9,7 +9,7 @@ use ipld_core::ipld::Ipld;
 use thiserror::Error as ThisError;
 use xrpc::error::Error as XrpcError;
 
-use crate::BskyReq;
+use crate::{rate_limit::Endpoint, BskyReq};
 
 #[derive(ThisError, Debug)]
 pub enum Error {}
@@ -48,6 +48,7 @@ impl BskyReq for Request {
   type ReqOutput = list_convos::OutputData;
   type ReqError = list_convos::Error;
   type HandledError = Error;
+  const ENDPOINT: Endpoint = Endpoint::Chat;
 
   fn get_params(self) -> Self::ReqParams {
     list_convos::Parameters {
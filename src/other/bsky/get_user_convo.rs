@@ -9,7 +9,7 @@ use ipld_core::ipld::Ipld;
 use thiserror::Error as ThisError;
 use xrpc::error::Error as XrpcError;
 
-use crate::BskyReq;
+use crate::{rate_limit::Endpoint, BskyReq};
 
 #[derive(ThisError, Debug)]
 pub enum Error {}
@@ -38,6 +38,7 @@ impl BskyReq for Request {
   type ReqOutput = get_convo_for_members::OutputData;
   type ReqError = get_convo_for_members::Error;
   type HandledError = Error;
+  const ENDPOINT: Endpoint = Endpoint::Chat;
 
   fn get_params(self) -> Self::ReqParams {
     Self::ReqParams {
@@ -1,53 +1,73 @@
-pub mod get_last_post_time;
+mod concurrency;
+mod feed_post;
 pub mod get_messages;
+pub mod get_posts_since;
 pub mod get_profile;
 pub mod get_profiles;
 pub mod get_unread_convos;
 pub mod get_user_convo;
 mod login;
+mod public_api;
+mod rate_limit;
 pub mod read_convo;
 pub mod send_message;
 
 use async_once::AsyncOnce;
 use atrium_api::types::Object;
 use atrium_xrpc::{
-  error::{Error as XrpcError, XrpcError as XrpcErrorResponse, XrpcErrorKind},
+  error::{Error as XrpcError, ErrorResponseBody, XrpcError as XrpcErrorResponse, XrpcErrorKind},
   http::StatusCode,
 };
 use bsky_sdk::BskyAgent;
 use lazy_static::lazy_static;
-use std::{sync::Arc, time::Duration};
+use std::{
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
 use thiserror::Error as ThisError;
-use tokio::{sync::RwLock, time::sleep};
+use tokio::{
+  sync::RwLock,
+  time::{sleep, Instant},
+};
 use tracing::{event, Level};
 use utils::Did;
 
+pub use concurrency::Access;
+pub use feed_post::FeedPost;
+use rate_limit::Endpoint;
+pub use rate_limit::RateLimitedClient;
+
+/// The bot's agent, whose requests are tracked against the rate limits.
+pub type Agent = BskyAgent<RateLimitedClient>;
+
 lazy_static! {
   pub static ref BSKY: AsyncOnce<RwLock<Bsky>> = AsyncOnce::new(Bsky::init());
 }
 static MAXIMUM_RETRIES: i8 = 4;
 static RETRY_DELAY: u64 = 15;
+/// Whether there's a logged in agent, kept apart so it can be checked without waiting on a login.
+static AGENT_VALID: AtomicBool = AtomicBool::new(false);
 
 pub struct Bsky {
-  agent: Option<Arc<BskyAgent>>,
+  agent: Option<Arc<Agent>>,
   agent_id: Option<Did>,
-  // last_action: DateTime<Utc>,
 }
 impl Bsky {
   async fn init() -> RwLock<Self> {
-    // #[expect(clippy::unwrap_used)] // Constant, should never fail
-    // let last_action = DateTime::<Utc>::from_timestamp(0, 0).unwrap();
     let (agent, did) = Self::retry_until_get_agent().await;
     let bsky = Self {
       agent: Some(Arc::new(agent)),
       agent_id: Some(did),
-      // last_action,
     };
+    AGENT_VALID.store(true, Ordering::Relaxed);
     RwLock::new(bsky)
   }
 
   #[expect(clippy::cognitive_complexity)]
-  async fn retry_until_get_agent() -> (BskyAgent, Did) {
+  async fn retry_until_get_agent() -> (Agent, Did) {
     event!(Level::INFO, "Logging in...");
 
     let agent;
@@ -78,19 +98,29 @@ impl Bsky {
 
   pub async fn invalidate_agent() {
     let mut bsky = BSKY.get().await.write().await;
+    AGENT_VALID.store(false, Ordering::Relaxed);
     bsky.agent = None;
     bsky.agent_id = None;
   }
 
   async fn revalidate_agent() {
     let mut bsky = BSKY.get().await.write().await;
+    metrics::BSKY_RELOGINS.inc();
     let (agent, did) = Self::retry_until_get_agent().await;
+    AGENT_VALID.store(true, Ordering::Relaxed);
     bsky.agent = Some(Arc::new(agent));
     bsky.agent_id = Some(did);
   }
 
+  /// Whether the agent is currently logged in, i.e. neither logging in for the first time
+  /// nor invalidated after its session expired.
+  #[must_use]
+  pub fn is_agent_valid() -> bool {
+    AGENT_VALID.load(Ordering::Relaxed)
+  }
+
   #[expect(clippy::missing_panics_doc)] // False positive because of unwrap
-  pub async fn get_agent() -> Arc<BskyAgent> {
+  pub async fn get_agent() -> Arc<Agent> {
     let bsky = BSKY.get().await.read().await;
     match &bsky.agent {
       Some(agent) => agent.clone(),
@@ -150,6 +180,15 @@ pub enum Error<Other> {
   Other(#[from] Other),
 }
 
+/// Why an attempt at issuing a request failed.
+enum Failure<E> {
+  /// The session has expired, so the agent was invalidated in order to be re-issued.
+  SessionExpired,
+  /// The rate limit was hit, so the request should only be retried once it resets.
+  RateLimited,
+  Error(Error<E>),
+}
+
 trait BskyReq {
   type ReqParams: Clone;
   type ReqOutput;
@@ -157,18 +196,37 @@ trait BskyReq {
   type HandledError: std::error::Error + std::fmt::Debug;
   const PER_REQ_MAX_RETRIES: u8 = 3;
   const ON_FAILURE_DELAY: u64 = 150; // 150 Milliseconds
+  /// Which rate limit budget the request is taken from.
+  const ENDPOINT: Endpoint = Endpoint::AppView;
+  /// Which concurrency limit the request counts towards.
+  const ACCESS: Access = Access::Read;
+
+  /// The name of the request in the metrics, i.e. the name of its module.
+  fn name() -> &'static str {
+    let path = std::any::type_name::<Self>();
+    path.rsplit("::").nth(1).unwrap_or(path)
+  }
 
   fn get_params(self) -> Self::ReqParams;
   async fn request(
     params: Self::ReqParams,
   ) -> Result<Object<Self::ReqOutput>, atrium_xrpc::Error<Self::ReqError>>;
   fn handle_xrpc_custom_error(err: Self::ReqError) -> Option<Error<Self::HandledError>>;
+  /// Handles the errors that aren't part of the request's lexicon, e.g. the ones telling that
+  /// an account is unavailable. None are handled by default, so they're treated as `Api` errors.
+  fn handle_xrpc_undefined_error(_err: &ErrorResponseBody) -> Option<Error<Self::HandledError>> {
+    None
+  }
 
   /// This method attempts to issue the request and handle any errors that might occur.
   /// It retries the request if it fails, up to `PER_REQ_MAX_RETRIES` times.
   /// This is done to prevent singular failures to completely run the workflow of our bot.
   /// It also handles authentication errors, by reauthenticating.
+  /// Requests are delayed whenever the rate limit budget of their `ENDPOINT` runs low, and
+  /// when rate limited, retried once the limit resets, also up to `PER_REQ_MAX_RETRIES` times.
+  /// They also wait for their turn whenever too many requests of the same `ACCESS` are in flight.
   /// At the end, if everything worked as expected, it returns the output of the request.
+  /// Every request, retry and outcome is counted in the metrics.
   ///
   /// # Errors
   /// Any expected errors that should be treated somewhere else, or (rarely) any persistent
@@ -178,31 +236,73 @@ trait BskyReq {
   where
     Self: Sized,
   {
-    // Commented out until I get rate limited at least once
-    // Unlikely to ever happen given the ping.
-    // TODO: Analyse how the ATProto APIs handle rate limiting and implement a better solution.
-    // minimum_delay().await;
+    let started_at = Instant::now();
+    let result = Self::act_with_retries(Self::get_params(self)).await;
+
+    let outcome = match &result {
+      Ok(_) => "ok",
+      Err(Error::Api) => "api",
+      Err(Error::BskyBug) => "bsky_bug",
+      Err(Error::Other(_)) => "handled",
+    };
+    metrics::BSKY_REQUESTS
+      .with_label_values(&[Self::name(), outcome])
+      .inc();
+    metrics::BSKY_REQUEST_DURATION
+      .with_label_values(&[Self::name()])
+      .observe(started_at.elapsed().as_secs_f64());
+    result
+  }
 
+  /// The retry loop of `act`.
+  ///
+  /// # Errors
+  /// The same as `act`.
+  async fn act_with_retries(
+    params: Self::ReqParams,
+  ) -> Result<Self::ReqOutput, Error<Self::HandledError>> {
     let mut failed_attempts = 0;
+    let mut rate_limited_attempts = 0;
 
-    let params = Self::get_params(self);
     loop {
-      match Self::attempt(params.clone()).await {
-        Err(Some(Error::Api) | None) => {
+      rate_limit::acquire(Self::ENDPOINT).await;
+      let permit = concurrency::acquire(Self::ACCESS).await;
+      let result = Self::attempt(params.clone()).await;
+      // Not held while waiting to retry
+      drop(permit);
+      let reason = match result {
+        Err(Failure::RateLimited) => {
+          // The wait for the limit to reset happens when acquiring the next attempt
+          if rate_limited_attempts >= Self::PER_REQ_MAX_RETRIES {
+            return Err(Error::Api);
+          }
+          rate_limited_attempts += 1;
+          "rate_limited"
+        }
+        Err(Failure::SessionExpired) => {
           if !Self::handle_error(&mut failed_attempts).await {
             return Err(Error::Api);
           }
-          continue;
+          "session_expired"
         }
-        Err(Some(Error::BskyBug)) => {
+        Err(Failure::Error(Error::Api)) => {
+          if !Self::handle_error(&mut failed_attempts).await {
+            return Err(Error::Api);
+          }
+          "error"
+        }
+        Err(Failure::Error(Error::BskyBug)) => {
           if !Self::handle_error(&mut failed_attempts).await {
             return Err(Error::BskyBug);
           }
-          continue;
+          "error"
         }
-        Err(Some(err)) => return Err(err),
+        Err(Failure::Error(err)) => return Err(err),
         Ok(output) => return Ok(output),
-      }
+      };
+      metrics::BSKY_RETRIES
+        .with_label_values(&[Self::name(), reason])
+        .inc();
     }
   }
 
@@ -222,26 +322,33 @@ trait BskyReq {
   /// Method for processing all kinds of error that might happen in the client or the request.
   ///
   /// # Returns
-  /// `Some(Error)` if the error was handled, `None` if the session has expired so that it can be
-  /// re-issued.
+  /// The reason the attempt failed, so that it can be retried accordingly.
   async fn attempt(
     params: Self::ReqParams,
-  ) -> Result<Self::ReqOutput, Option<Error<Self::HandledError>>> {
+  ) -> Result<Self::ReqOutput, Failure<Self::HandledError>> {
     let err = match Self::request(params).await {
       Ok(output) => return Ok(output.data),
       Err(XrpcError::XrpcResponse(XrpcErrorResponse::<Self::ReqError> { status, error })) => {
         let status = status.as_u16();
+        let handled = match &error {
+          Some(XrpcErrorKind::Undefined(body)) => Self::handle_xrpc_undefined_error(body),
+          _ => None,
+        };
         if status == StatusCode::UNAUTHORIZED.as_u16() {
           Bsky::invalidate_agent().await;
-          None
+          return Err(Failure::SessionExpired);
+        } else if status == StatusCode::TOO_MANY_REQUESTS.as_u16() {
+          return Err(Failure::RateLimited);
         } else if let Some(XrpcErrorKind::Custom(e)) = error {
-          Self::handle_xrpc_custom_error(e)
+          Self::handle_xrpc_custom_error(e).unwrap_or(Error::Api)
+        } else if let Some(e) = handled {
+          e
         } else {
           event!(
             Level::WARN,
             "(Notice) Failed to issue request, API Error. Status Code: {status}. Error: {error:?}."
           );
-          Some(Error::Api)
+          Error::Api
         }
       }
       Err(XrpcError::HttpRequest(e)) => {
@@ -249,54 +356,37 @@ trait BskyReq {
           Level::WARN,
           "(Notice) Failed to issue request, API Error: {e:?}"
         );
-        Some(Error::Api)
+        Error::Api
       }
       Err(XrpcError::HttpClient(e)) => {
         event!(
           Level::WARN,
           "(Notice) Failed to issue request, API Error: {e:?}"
         );
-        Some(Error::Api)
+        Error::Api
       }
       Err(XrpcError::SerdeJson(e)) => {
         event!(
           Level::WARN,
           "(Notice) Failed to issue request, Bsky Error (SerdeJson): {e:?}"
         );
-        Some(Error::BskyBug)
+        Error::BskyBug
       }
       Err(XrpcError::SerdeHtmlForm(e)) => {
         event!(
           Level::WARN,
           "(Notice) Failed to issue request, Bsky Error (SerdeHtmlForm): {e:?}"
         );
-        Some(Error::BskyBug)
+        Error::BskyBug
       }
       Err(XrpcError::UnexpectedResponseType) => {
         event!(
           Level::WARN,
           "(Notice) Failed to issue request, Bsky Error (UnexpectedResponseType)"
         );
-        Some(Error::BskyBug)
+        Error::BskyBug
       }
     };
-    Err(err)
+    Err(Failure::Error(err))
   }
 }
-
-// static MINIMUM_DELAY: u64 = 10; // 10 Milliseconds
-
-// async fn minimum_delay() {
-//   let mut context: RwLockWriteGuard<'_, Bsky> = BSKY.get().await.write().await;
-//   let current = chrono::offset::Utc::now();
-//   #[expect(clippy::unwrap_used)] // Guaranteed not to overflow
-//   let elapsed: u64 = current
-//     .signed_duration_since(context.last_action)
-//     .num_milliseconds()
-//     .try_into()
-//     .unwrap();
-//   if elapsed < MINIMUM_DELAY {
-//     sleep(Duration::from_millis(MINIMUM_DELAY - elapsed)).await;
-//   }
-//   context.last_action = current;
-// }
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use bsky_sdk::{
   agent::config::{Config, FileStore},
@@ -6,12 +6,15 @@ use bsky_sdk::{
   error::GenericXrpcError,
   BskyAgent, Error as BskyError,
 };
-use environment::{BOT_PASSWORD, BOT_USERNAME, WORKSPACE_DIR};
+use environment::{owned_var_or_else, BOT_PASSWORD, BOT_USERNAME, WORKSPACE_DIR};
 use tracing::{event, Level};
 use utils::Did;
 
+use crate::{Agent, RateLimitedClient};
+
 /// Attempt to login to the Bsky API.
-/// First, it will try to load the agent from a config file.
+/// First, it will try to load the agent from a config file in `SESSION_DIR`, if set,
+/// or in the workspace directory.
 /// Then, if that fails, it will attempt to login and create a new session.
 ///
 /// # Returns
@@ -19,8 +22,9 @@ use utils::Did;
 ///
 /// # Errors
 /// If the login fails.
-pub async fn act() -> Result<(BskyAgent, Did), BskyError> {
-  let path = (*WORKSPACE_DIR).join(format!("{}-config.json", *BOT_USERNAME));
+pub async fn act() -> Result<(Agent, Did), BskyError> {
+  let dir = owned_var_or_else("SESSION_DIR", || PathBuf::from(*WORKSPACE_DIR));
+  let path = dir.join(format!("{}-config.json", *BOT_USERNAME));
   let file_store = FileStore::new(path);
 
   match try_load_from_config(&file_store).await {
@@ -30,8 +34,17 @@ pub async fn act() -> Result<(BskyAgent, Did), BskyError> {
 }
 
 /// Attempt to login to the Bsky API and saves the session to a config file.
-async fn do_auth(file_store: &FileStore) -> Result<(BskyAgent, Did), BskyError> {
-  let agent = BskyAgent::builder().build().await?;
+/// Logs in to `BSKY_SERVICE_URL`, if set, instead of the default service.
+async fn do_auth(file_store: &FileStore) -> Result<(Agent, Did), BskyError> {
+  let endpoint = owned_var_or_else("BSKY_SERVICE_URL", || Config::default().endpoint);
+  let agent = BskyAgent::builder()
+    .client(RateLimitedClient::new(endpoint.clone()))
+    .config(Config {
+      endpoint,
+      ..Default::default()
+    })
+    .build()
+    .await?;
   agent.login(*BOT_USERNAME, *BOT_PASSWORD).await?;
   let config = agent.to_config().await;
   #[expect(clippy::unwrap_used)] // Just logged in
@@ -41,11 +54,15 @@ async fn do_auth(file_store: &FileStore) -> Result<(BskyAgent, Did), BskyError>
 }
 
 /// Attempt to load the Bsky agent from a config file, if it exists.
-async fn try_load_from_config(file_store: &FileStore) -> Result<(BskyAgent, Did), BskyError> {
+async fn try_load_from_config(file_store: &FileStore) -> Result<(Agent, Did), BskyError> {
   if let Ok(config) = Config::load(file_store).await {
     #[expect(clippy::unwrap_used)] // Restored from session
     let did = config.session.as_ref().unwrap().did.clone();
-    let agent = BskyAgent::builder().config(config).build().await?;
+    let agent = BskyAgent::builder()
+      .client(RateLimitedClient::new(config.endpoint.clone()))
+      .config(config)
+      .build()
+      .await?;
     event!(
       Level::DEBUG,
       "Recovered previous configs for user {}!",
@@ -62,7 +79,7 @@ async fn try_load_from_config(file_store: &FileStore) -> Result<(BskyAgent, Did)
 async fn handle_bsky_error(
   file_store: &FileStore,
   e: BskyError,
-) -> Result<(BskyAgent, Did), BskyError> {
+) -> Result<(Agent, Did), BskyError> {
   match e {
     BskyError::Xrpc(e) => {
       if let GenericXrpcError::Response { status, .. } = *e {
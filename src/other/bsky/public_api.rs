@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use atrium_xrpc::{
+  http::{Request, Response},
+  HttpClient, XrpcClient,
+};
+use atrium_xrpc_client::reqwest::ReqwestClient;
+use environment::owned_var_or_else;
+
+/// The public `AppView` that `bsky_sdk` sends its own unauthenticated requests to.
+const PUBLIC_API_ENDPOINT: &str = "https://public.api.bsky.app";
+
+/// Client for the requests `bsky_sdk` makes to the public `AppView` by itself, such as
+/// resolving mentions when detecting rich text facets.
+///
+/// Those always target `PUBLIC_API_ENDPOINT`, so they're redirected to `BSKY_PUBLIC_API_URL`
+/// instead, if set. E.g. to a fake server in tests.
+pub struct PublicApiClient {
+  inner: ReqwestClient,
+  endpoint: String,
+}
+impl PublicApiClient {
+  pub fn new() -> Self {
+    let endpoint = owned_var_or_else("BSKY_PUBLIC_API_URL", || PUBLIC_API_ENDPOINT.to_string());
+    Self {
+      inner: ReqwestClient::new(&endpoint),
+      endpoint,
+    }
+  }
+}
+
+#[async_trait]
+impl HttpClient for PublicApiClient {
+  async fn send_http(
+    &self,
+    mut request: Request<Vec<u8>>,
+  ) -> Result<Response<Vec<u8>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let uri = request.uri().to_string();
+    if let Some(path) = uri.strip_prefix(PUBLIC_API_ENDPOINT) {
+      *request.uri_mut() = format!("{}{path}", self.endpoint).parse()?;
+    }
+    self.inner.send_http(request).await
+  }
+}
+
+impl XrpcClient for PublicApiClient {
+  fn base_uri(&self) -> String {
+    self.endpoint.clone()
+  }
+}
@@ -0,0 +1,327 @@
+use std::{sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use atrium_xrpc::{
+  http::{HeaderMap, Request, Response, StatusCode},
+  HttpClient, XrpcClient,
+};
+use atrium_xrpc_client::reqwest::ReqwestClient;
+use chrono::{DateTime, TimeDelta, Utc};
+use lazy_static::lazy_static;
+use tokio::time::sleep;
+use tracing::{event, Level};
+
+/// Below this many requests left in a window, requests are spread evenly until it resets.
+static LOW_BUDGET: u64 = 10;
+/// How long to wait after a 429 that says nothing about when to retry.
+static DEFAULT_RETRY_AFTER: i64 = 15; // 15 Seconds
+/// How far apart the requests held back by an exhausted budget are issued once it resets,
+/// so that the first one can report the new budget before the rest go out.
+static RESET_SPACING: i64 = 500; // 500 Milliseconds
+
+/// The families of endpoints the bot talks to, each rate limited on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+  /// Requests served by the `AppView`, e.g. profiles and feeds.
+  AppView,
+  /// Requests proxied to the chat service, e.g. convos and messages.
+  Chat,
+}
+impl Endpoint {
+  fn of(request: &Request<Vec<u8>>) -> Self {
+    if request.uri().path().starts_with("/xrpc/chat.bsky.") {
+      Self::Chat
+    } else {
+      Self::AppView
+    }
+  }
+
+  fn bucket(self) -> &'static Mutex<Bucket> {
+    match self {
+      Self::AppView => &APP_VIEW_BUCKET,
+      Self::Chat => &CHAT_BUCKET,
+    }
+  }
+}
+
+lazy_static! {
+  static ref APP_VIEW_BUCKET: Mutex<Bucket> = Mutex::default();
+  static ref CHAT_BUCKET: Mutex<Bucket> = Mutex::default();
+}
+
+/// A token bucket refilled by the server: the tokens are the requests it reports to be left
+/// in the current window, which are taken as requests are issued until the next report.
+#[derive(Debug, Default)]
+struct Bucket {
+  /// `None` while unknown, i.e. until the first report or after the window resets.
+  remaining: Option<u64>,
+  reset: DateTime<Utc>,
+  /// The earliest a request may be issued, so that delayed requests are spread out.
+  next_slot: DateTime<Utc>,
+}
+impl Bucket {
+  /// Takes a token, returning how long to wait before issuing the request.
+  fn take(&mut self, now: DateTime<Utc>) -> TimeDelta {
+    let Some(remaining) = self.remaining else {
+      return TimeDelta::zero();
+    };
+    if now >= self.reset {
+      self.remaining = None;
+      return TimeDelta::zero();
+    }
+    if remaining > LOW_BUDGET {
+      self.remaining = Some(remaining - 1);
+      return TimeDelta::zero();
+    }
+
+    let slot = if remaining == 0 {
+      // Out of budget, so everything waits for the window to reset, one after the other
+      let slot = self.next_slot.max(self.reset);
+      self.next_slot = slot + TimeDelta::milliseconds(RESET_SPACING);
+      slot
+    } else {
+      #[expect(clippy::unwrap_used)] // Never above `LOW_BUDGET`
+      let interval = (self.reset - now) / i32::try_from(remaining + 1).unwrap();
+      self.remaining = Some(remaining - 1);
+      let slot = self.next_slot.max(now);
+      self.next_slot = slot + interval;
+      slot
+    };
+    slot - now
+  }
+
+  /// Updates the budget from the `ratelimit-*` headers of a response, if any.
+  fn update(&mut self, status: StatusCode, headers: &HeaderMap, now: DateTime<Utc>) {
+    let header = |name: &str| {
+      headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok())
+    };
+    let reset = header("ratelimit-reset").and_then(|reset| DateTime::from_timestamp(reset, 0));
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+      let retry_at = header("retry-after")
+        .map(|seconds| now + TimeDelta::seconds(seconds))
+        .or(reset)
+        .unwrap_or_else(|| now + TimeDelta::seconds(DEFAULT_RETRY_AFTER));
+      self.remaining = Some(0);
+      self.reset = retry_at.max(now);
+      return;
+    }
+
+    if let (Some(remaining), Some(reset)) = (header("ratelimit-remaining"), reset) {
+      self.remaining = Some(remaining.try_into().unwrap_or_default());
+      self.reset = reset;
+    }
+  }
+}
+
+/// Waits until the budget of an endpoint family allows for another request.
+pub async fn acquire(endpoint: Endpoint) {
+  #[expect(clippy::unwrap_used)] // Only poisoned if another thread panicked
+  let delay = endpoint.bucket().lock().unwrap().take(Utc::now());
+  if delay > TimeDelta::zero() {
+    event!(
+      Level::DEBUG,
+      "Rate limit budget for {endpoint:?} is low, delaying request by {}ms.",
+      delay.num_milliseconds()
+    );
+    sleep(delay.to_std().unwrap_or(Duration::ZERO)).await;
+  }
+}
+
+/// Client for the bot's agent, which keeps track of each endpoint family's rate limit budget
+/// from the headers of every response.
+pub struct RateLimitedClient {
+  inner: ReqwestClient,
+  endpoint: String,
+}
+impl RateLimitedClient {
+  #[must_use]
+  pub fn new(endpoint: String) -> Self {
+    Self {
+      inner: ReqwestClient::new(&endpoint),
+      endpoint,
+    }
+  }
+}
+
+#[async_trait]
+impl HttpClient for RateLimitedClient {
+  async fn send_http(
+    &self,
+    request: Request<Vec<u8>>,
+  ) -> Result<Response<Vec<u8>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let endpoint = Endpoint::of(&request);
+    let response = self.inner.send_http(request).await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+      event!(Level::WARN, "(Notice) Rate limited by {endpoint:?}.");
+    }
+    #[expect(clippy::unwrap_used)] // Only poisoned if another thread panicked
+    endpoint
+      .bucket()
+      .lock()
+      .unwrap()
+      .update(response.status(), response.headers(), Utc::now());
+    Ok(response)
+  }
+}
+
+impl XrpcClient for RateLimitedClient {
+  fn base_uri(&self) -> String {
+    self.endpoint.clone()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use atrium_xrpc::http::{HeaderMap, HeaderValue, StatusCode};
+  use chrono::{DateTime, TimeDelta, Utc};
+
+  use super::{Bucket, DEFAULT_RETRY_AFTER, LOW_BUDGET, RESET_SPACING};
+
+  fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+  }
+
+  fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+      headers.insert(*name, HeaderValue::from_str(value).expect("valid header"));
+    }
+    headers
+  }
+
+  fn bucket(remaining: u64, reset_in: TimeDelta) -> Bucket {
+    Bucket {
+      remaining: Some(remaining),
+      reset: now() + reset_in,
+      next_slot: now(),
+    }
+  }
+
+  #[test]
+  fn update_reads_the_budget_from_headers() {
+    let reset = (now() + TimeDelta::seconds(300)).timestamp().to_string();
+    let cases = [
+      (
+        vec![
+          ("ratelimit-remaining", "42"),
+          ("ratelimit-reset", reset.as_str()),
+        ],
+        Some(42),
+      ),
+      (
+        vec![
+          ("ratelimit-remaining", " 7 "),
+          ("ratelimit-reset", reset.as_str()),
+        ],
+        Some(7),
+      ),
+      // Negative budgets are taken as exhausted
+      (
+        vec![
+          ("ratelimit-remaining", "-1"),
+          ("ratelimit-reset", reset.as_str()),
+        ],
+        Some(0),
+      ),
+      // Incomplete or invalid headers are ignored
+      (vec![("ratelimit-remaining", "42")], None),
+      (vec![("ratelimit-reset", reset.as_str())], None),
+      (
+        vec![
+          ("ratelimit-remaining", "many"),
+          ("ratelimit-reset", reset.as_str()),
+        ],
+        None,
+      ),
+      (vec![], None),
+    ];
+    for (pairs, expected) in cases {
+      let mut bucket = Bucket::default();
+      bucket.update(StatusCode::OK, &headers(&pairs), now());
+      assert_eq!(bucket.remaining, expected, "{pairs:?}");
+      if expected.is_some() {
+        assert_eq!(bucket.reset, now() + TimeDelta::seconds(300), "{pairs:?}");
+      }
+    }
+  }
+
+  #[test]
+  fn update_waits_as_told_after_too_many_requests() {
+    let reset = (now() + TimeDelta::seconds(300)).timestamp().to_string();
+    let past = (now() - TimeDelta::seconds(300)).timestamp().to_string();
+    let cases = [
+      // Retry-After is preferred over the window's reset
+      (
+        vec![("retry-after", "30"), ("ratelimit-reset", reset.as_str())],
+        TimeDelta::seconds(30),
+      ),
+      (
+        vec![("ratelimit-reset", reset.as_str())],
+        TimeDelta::seconds(300),
+      ),
+      (vec![], TimeDelta::seconds(DEFAULT_RETRY_AFTER)),
+      (
+        vec![("retry-after", "soon")],
+        TimeDelta::seconds(DEFAULT_RETRY_AFTER),
+      ),
+      // Never earlier than now
+      (vec![("ratelimit-reset", past.as_str())], TimeDelta::zero()),
+    ];
+    for (pairs, expected) in cases {
+      let mut bucket = Bucket::default();
+      bucket.update(StatusCode::TOO_MANY_REQUESTS, &headers(&pairs), now());
+      assert_eq!(bucket.remaining, Some(0), "{pairs:?}");
+      assert_eq!(bucket.reset, now() + expected, "{pairs:?}");
+    }
+  }
+
+  #[test]
+  fn take_is_free_while_the_budget_is_unknown_or_high() {
+    let mut unknown = Bucket::default();
+    assert_eq!(unknown.take(now()), TimeDelta::zero());
+    assert_eq!(unknown.remaining, None);
+
+    let mut high = bucket(LOW_BUDGET + 2, TimeDelta::seconds(60));
+    assert_eq!(high.take(now()), TimeDelta::zero());
+    assert_eq!(high.take(now()), TimeDelta::zero());
+    assert_eq!(high.remaining, Some(LOW_BUDGET));
+  }
+
+  #[test]
+  fn take_spreads_a_low_budget_until_the_reset() {
+    // Each request is spaced from the previous one by what's left of the window, split
+    // between the requests left and one more, to leave room for whoever comes after
+    let mut bucket = bucket(3, TimeDelta::seconds(40));
+    assert_eq!(bucket.take(now()), TimeDelta::zero());
+    assert_eq!(bucket.take(now()), TimeDelta::seconds(40) / 4);
+    assert_eq!(
+      bucket.take(now()),
+      TimeDelta::seconds(40) / 4 + TimeDelta::seconds(40) / 3
+    );
+    assert_eq!(bucket.remaining, Some(0));
+  }
+
+  #[test]
+  fn take_spaces_out_requests_held_until_the_reset() {
+    let mut bucket = bucket(0, TimeDelta::seconds(30));
+    for i in 0..5 {
+      assert_eq!(
+        bucket.take(now()),
+        TimeDelta::seconds(30) + TimeDelta::milliseconds(RESET_SPACING) * i
+      );
+    }
+    assert_eq!(bucket.remaining, Some(0));
+  }
+
+  #[test]
+  fn take_forgets_the_budget_once_the_window_resets() {
+    let mut bucket = bucket(0, TimeDelta::seconds(30));
+    let later = now() + TimeDelta::seconds(30);
+    assert_eq!(bucket.take(later), TimeDelta::zero());
+    assert_eq!(bucket.remaining, None);
+  }
+}
@@ -6,7 +6,7 @@ use ipld_core::ipld::Ipld;
 use thiserror::Error as ThisError;
 use xrpc::error::Error as XrpcError;
 
-use crate::BskyReq;
+use crate::{rate_limit::Endpoint, Access, BskyReq};
 
 #[derive(ThisError, Debug)]
 pub enum Error {}
@@ -28,6 +28,8 @@ impl BskyReq for Request {
   type ReqOutput = update_read::OutputData;
   type ReqError = update_read::Error;
   type HandledError = Error;
+  const ENDPOINT: Endpoint = Endpoint::Chat;
+  const ACCESS: Access = Access::Write;
 
   fn get_params(self) -> Self::ReqParams {
     Self::ReqParams {
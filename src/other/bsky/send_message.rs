@@ -1,11 +1,13 @@
 use super::Bsky;
 use atrium_api::{
   agent::bluesky::AtprotoServiceType,
+  app::bsky::embed::record,
   chat::bsky::convo::{
-    defs::{MessageInput, MessageInputData, MessageViewData},
+    defs::{MessageInput, MessageInputData, MessageInputEmbedRefs, MessageViewData},
     send_message,
   },
-  types::Object,
+  com::atproto::repo::strong_ref,
+  types::{Object, Union},
   xrpc,
 };
 use bsky_sdk::{rich_text::RichText, Error as BskyError};
@@ -14,7 +16,7 @@ use thiserror::Error as ThisError;
 use tracing::{event, Level};
 use xrpc::error::Error as XrpcError;
 
-use crate::BskyReq;
+use crate::{public_api::PublicApiClient, rate_limit::Endpoint, Access, BskyReq};
 
 #[derive(ThisError, Debug)]
 pub enum Error {
@@ -23,7 +25,8 @@ pub enum Error {
 }
 
 /// Method to send a message to a conversation.
-/// Optionally parses any rich text in the message.
+/// Optionally parses any rich text in the message, and embeds a record (e.g. a post)
+/// so that it's rendered inline.
 ///
 /// # Errors
 ///
@@ -32,20 +35,32 @@ pub async fn act(
   convo_id: String,
   mut msg_text: String,
   with_rich_text: bool,
+  embed: Option<strong_ref::Main>,
 ) -> Result<MessageViewData, super::Error<Error>> {
   let mut msg_facets = None;
   if with_rich_text {
-    let RichText { facets, text } = RichText::new_with_detect_facets(msg_text)
+    let mut rich_text = RichText {
+      text: msg_text,
+      facets: None,
+    };
+    rich_text
+      .detect_facets(PublicApiClient::new())
       .await
       .map_err(handle_rich_text_error)?;
-    msg_facets = facets;
-    msg_text = text;
+    msg_facets = rich_text.facets;
+    msg_text = rich_text.text;
   }
 
+  let msg_embed = embed.map(|record| {
+    Union::Refs(MessageInputEmbedRefs::AppBskyEmbedRecordMain(Box::new(
+      record::MainData { record }.into(),
+    )))
+  });
+
   let message = MessageInputData {
     facets: msg_facets,
     text: msg_text,
-    embed: None,
+    embed: msg_embed,
   };
   Request { convo_id, message }.act().await
 }
@@ -59,6 +74,8 @@ impl BskyReq for Request {
   type ReqOutput = MessageViewData;
   type ReqError = send_message::Error;
   type HandledError = Error;
+  const ENDPOINT: Endpoint = Endpoint::Chat;
+  const ACCESS: Access = Access::Write;
 
   fn get_params(self) -> Self::ReqParams {
     send_message::Input {
@@ -8,10 +8,45 @@
 //!   * Defaults to `INFO`. Used at `utils::init_logging`.
 //! - `LOG_DIRECTORY` - The directory where the log files are stored.
 //!   * Defaults to `/var/log/post_watcher`. Used at `utils::init_logging`.
-//! - `DATABASE_URL` - The URL to the database.
+//! - `DATABASE_URL` - The URL to the database. A `postgres://` URL picks the Postgres backend,
+//!   which requires the `postgres` feature.
 //!   * Defaults to `sqlite://data.db`. Used at `Database::init`.
 //! - `DB_CONN_POOL_MAX` - The maximum number of connections to the database.
 //!   * Defaults to `100`. Used at `Database::init`.
+//! - `MAX_BACKFILL_HOURS` - How far back to look for posts made while the bot was down.
+//!   * Defaults to `24`. Used at `backfill::begin`.
+//! - `JETSTREAM_URL` - The Jetstream instance to listen to for new posts.
+//!   * Defaults to `wss://jetstream2.us-east.bsky.network/subscribe`. Used at `jetstream_listener::begin`.
+//! - `POLL_INTERVAL_MIN` - The least time, in seconds, between polls of a watched user in `polling`
+//!   mode. The most active users are polled this often.
+//!   * Defaults to `15`. Used at `user_watcher`.
+//! - `POLL_INTERVAL_MAX` - The most time, in seconds, between polls of a watched user in `polling`
+//!   mode. The least active users are polled this often.
+//!   * Defaults to `600`. Used at `user_watcher`.
+//! - `MAX_SUSPENSION_DAYS` - How long, in days, a watched user that can't be reached may stay
+//!   suspended before they're deemed gone and no longer watched, in `polling` mode.
+//!   * Defaults to `7`. Used at `user_watcher`.
+//! - `BSKY_SERVICE_URL` - The service the bot logs in to and sends its requests through.
+//!   * Defaults to `https://bsky.social`. Used at `do_auth`.
+//! - `BSKY_PUBLIC_API_URL` - The public API used to resolve mentions in the bot's messages.
+//!   * Defaults to `https://public.api.bsky.app`. Used at `PublicApiClient::new`.
+//! - `SESSION_DIR` - The directory where the bot's session is cached between runs.
+//!   * Defaults to `WORKSPACE_DIR`. Used at `bsky::login`.
+//! - `BSKY_MAX_CONCURRENT_READS` - How many requests that only read data may be in flight at once.
+//!   * Defaults to `20`. Used at `bsky::concurrency`.
+//! - `BSKY_MAX_CONCURRENT_WRITES` - How many requests that change data, e.g. sending messages, may be
+//!   in flight at once.
+//!   * Defaults to `5`. Used at `bsky::concurrency`.
+//! - `MAX_QUEUED_COMMANDS` - How many commands each user may have waiting to be handled at once.
+//!   Any more are rejected with a reply.
+//!   * Defaults to `5`. Used at `pending_messages::add`.
+//! - `JOB_MAX_RESTARTS` - How many times a job may be restarted within an hour before the bot gives
+//!   up and exits.
+//!   * Defaults to `10`. Used at `supervisor::supervise`.
+//! - `HTTP_SERVER_ADDR` - The address the HTTP server listens on, e.g. `0.0.0.0:9090`, which
+//!   exposes the bot's metrics at `/metrics`, its health checks at `/healthz` and `/readyz`, and
+//!   the state of its polls at `/debug/polls`.
+//!   * Does not have a default value, the server is only started if set. Used at `main`.
 
 use std::path::Path;
 
@@ -19,7 +54,7 @@ use std::path::Path;
 use anyhow::anyhow;
 use lazy_static::lazy_static;
 
-use crate::{owned_var_try, try_leak, var};
+use crate::{owned_var_or, owned_var_try, try_leak, var, IngestionMode};
 
 // Environment-agnostic variables
 
@@ -28,6 +63,13 @@ lazy_static! {
   /// they are watched and when they are unwatched. If the variable is set to anything,
   /// it will be considered as `false`. If it is unset, the feature is on by default.
   pub static ref TURN_OFF_WATCHED_NOTIFS: bool = owned_var_try::<String>("TURN_OFF_WATCHED_NOTIFS").is_ok();
+  /// Variable that determines whether the bot should tell watchers when a watched user's
+  /// handle changes. If the variable is set to anything, it will be considered as `false`.
+  /// If it is unset, the feature is on by default.
+  pub static ref TURN_OFF_HANDLE_CHANGE_NOTIFS: bool = owned_var_try::<String>("TURN_OFF_HANDLE_CHANGE_NOTIFS").is_ok();
+  /// How the bot finds out about new posts. Either `jetstream` or `polling`.
+  /// Defaults to `jetstream`, polling is kept as a fallback.
+  pub static ref INGESTION_MODE: IngestionMode = owned_var_or("INGESTION_MODE", IngestionMode::Jetstream);
   /// The bot username on The Atmosphere.
   pub static ref BOT_USERNAME: &'static str = var::<String, _>("BOT_USERNAME");
   /// The bot password or app password.
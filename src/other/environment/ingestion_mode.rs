@@ -0,0 +1,23 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// How the bot finds out about new posts from watched users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestionMode {
+  /// Listens to a Jetstream instance for new posts. One connection for all watched users.
+  Jetstream,
+  /// Polls each watched user's author feed periodically. Kept as a fallback.
+  Polling,
+}
+impl FromStr for IngestionMode {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "jetstream" => Ok(Self::Jetstream),
+      "polling" => Ok(Self::Polling),
+      _ => bail!("Unknown ingestion mode: {s}. Expected `jetstream` or `polling`."),
+    }
+  }
+}
@@ -1,9 +1,30 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
 
 mod environment;
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 pub use environment::*;
 
+mod ingestion_mode;
+pub use ingestion_mode::IngestionMode;
+
+/// Values read instead of the environment variables of the same names (check `set_overrides`).
+static OVERRIDES: OnceLock<HashMap<&'static str, String>> = OnceLock::new();
+
+/// Sets values to be read instead of the environment variables of the same names.
+///
+/// Lets tests configure the bot without changing the environment of the whole process.
+/// Must be called before the variables are first read, since most are only read once.
+///
+/// # Errors
+/// When the overrides were already set.
+pub fn set_overrides(
+  overrides: impl IntoIterator<Item = (&'static str, String)>,
+) -> Result<(), anyhow::Error> {
+  OVERRIDES
+    .set(overrides.into_iter().collect())
+    .map_err(|_| anyhow!("Environment overrides were already set!"))
+}
+
 /// Utility to attempt leaking a Box to your desired static reference type.
 fn try_leak<ToLeak, R: ?Sized>(
   to_leak: ToLeak,
@@ -26,7 +47,10 @@ pub fn owned_var_try<T: FromStr>(name: &'static str) -> Result<T, anyhow::Error>
 where
   anyhow::Error: From<<T as FromStr>::Err>,
 {
-  let var = std::env::var(name)?;
+  let var = match OVERRIDES.get().and_then(|overrides| overrides.get(name)) {
+    Some(var) => var.clone(),
+    None => std::env::var(name)?,
+  };
   if var.is_empty() {
     bail!("Empty environment variable {name}!");
   }
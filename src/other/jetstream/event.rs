@@ -0,0 +1,62 @@
+use atrium_api::types::string::Cid;
+use serde::Deserialize;
+
+/// A single message sent by Jetstream.
+///
+/// Only the fields relevant to the bot are deserialized, everything else is ignored.
+#[derive(Debug, Deserialize)]
+pub struct Event {
+  pub did: String,
+  pub time_us: u64,
+  #[serde(flatten)]
+  pub kind: Kind,
+}
+
+/// The kind of a Jetstream event, along with its payload.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Kind {
+  Commit {
+    commit: Commit,
+  },
+  Identity {
+    identity: Identity,
+  },
+  Account {
+    account: Account,
+  },
+  #[serde(other)]
+  Other,
+}
+
+/// A commit to a repository, representing a record being created, updated or deleted.
+#[derive(Debug, Deserialize)]
+pub struct Commit {
+  pub operation: Operation,
+  pub collection: String,
+  pub rkey: String,
+  pub cid: Option<Cid>,
+  pub record: Option<serde_json::Value>,
+}
+
+/// A change to a user's identity, e.g. their handle.
+#[derive(Debug, Deserialize)]
+pub struct Identity {
+  pub handle: Option<String>,
+}
+
+/// A change to the status of a user's account, e.g. it being deactivated.
+#[derive(Debug, Deserialize)]
+pub struct Account {
+  pub active: bool,
+  /// Why the account isn't active, e.g. `deactivated`, `takendown` or `deleted`.
+  pub status: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+  Create,
+  Update,
+  Delete,
+}
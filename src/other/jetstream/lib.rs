@@ -0,0 +1,7 @@
+//! A minimal client for Jetstream, a simplified JSON event stream of the Bluesky firehose.
+//! See <https://github.com/bluesky-social/jetstream> for the protocol.
+
+pub mod event;
+
+mod subscription;
+pub use subscription::*;
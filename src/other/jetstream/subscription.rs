@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use atrium_api::{app::bsky::feed::post::Record, types::string::Cid};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use thiserror::Error as ThisError;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+  connect_async,
+  tungstenite::{self, Message},
+  MaybeTlsStream, WebSocketStream,
+};
+use tracing::{event, Level};
+use utils::{is_reply, Did};
+
+use crate::event::{Account, Commit, Event, Identity, Kind, Operation};
+
+/// The collection of posts, the only one the bot is interested in.
+pub const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+#[derive(ThisError, Debug)]
+pub enum Error {
+  #[error("WebSocket error: {0}")]
+  WebSocket(#[from] tungstenite::Error),
+  #[error("Jetstream closed the connection")]
+  Closed,
+  #[error("No users are wanted, which Jetstream takes as everyone")]
+  NoneWanted,
+}
+
+/// A post that was just created by one of the wanted users.
+#[derive(Debug)]
+pub struct PostCreated {
+  pub did: Did,
+  pub rkey: String,
+  pub cid: Cid,
+  pub time_us: u64,
+  pub record: Record,
+}
+impl PostCreated {
+  /// The AT URI of the created post.
+  #[must_use]
+  pub fn uri(&self) -> String {
+    format!("at://{}/{POST_COLLECTION}/{}", self.did, self.rkey)
+  }
+
+  /// Whether the post is a reply to someone else. Replies within the author's own threads
+  /// are considered regular posts.
+  #[must_use]
+  pub fn is_reply(&self) -> bool {
+    self
+      .record
+      .reply
+      .as_ref()
+      .is_some_and(|reply| is_reply(&self.did, &reply.parent.uri))
+  }
+}
+
+/// Something that happened to one of the wanted users.
+#[derive(Debug)]
+#[expect(clippy::large_enum_variant)] // Posts are by far the most common updates
+pub enum Update {
+  Post(PostCreated),
+  /// The user's identity has changed, e.g. their handle. The handle is `None` when it
+  /// couldn't be verified.
+  IdentityChanged {
+    did: Did,
+    handle: Option<String>,
+    time_us: u64,
+  },
+  /// The status of the user's account has changed.
+  AccountChanged {
+    did: Did,
+    status: AccountStatus,
+    time_us: u64,
+  },
+}
+
+/// The status of a user's account, as reported by Jetstream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountStatus {
+  Active,
+  /// The account was deactivated, taken down or suspended, any of which might be reverted.
+  /// Holds the status reported, if any.
+  Unavailable(Option<String>),
+  Deleted,
+}
+
+/// A connection to Jetstream, subscribed to the posts of a set of users.
+pub struct Subscription {
+  stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+  wanted_dids: HashSet<Did>,
+}
+impl Subscription {
+  /// Connects to the Jetstream instance at `url` and subscribes to the posts of `wanted_dids`.
+  /// The connection is opened with `requireHello`, so that no events are sent before the
+  /// wanted DIDs are known by the server.
+  /// If a `cursor` (in unix microseconds) is given, events since then are replayed first.
+  ///
+  /// # Errors
+  ///
+  /// When the connection or the subscription message fails, or right away if `wanted_dids` is
+  /// empty, since Jetstream would send the posts of everyone instead.
+  pub async fn connect(
+    url: &str,
+    wanted_dids: HashSet<Did>,
+    cursor: Option<u64>,
+  ) -> Result<Self, Error> {
+    if wanted_dids.is_empty() {
+      return Err(Error::NoneWanted);
+    }
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let cursor = cursor.map_or_else(String::new, |cursor| format!("&cursor={cursor}"));
+    let url =
+      format!("{url}{separator}wantedCollections={POST_COLLECTION}&requireHello=true{cursor}");
+    let (stream, _) = connect_async(url).await?;
+
+    let mut subscription = Self {
+      stream,
+      wanted_dids: HashSet::new(),
+    };
+    subscription.update_wanted_dids(wanted_dids).await?;
+    Ok(subscription)
+  }
+
+  /// Replaces the set of users whose posts are being listened to, without reconnecting.
+  ///
+  /// # Errors
+  ///
+  /// When the options update fails to be sent, or right away if `wanted_dids` is empty, since
+  /// Jetstream would send the posts of everyone instead. The wanted users are kept as they
+  /// were in either case.
+  pub async fn update_wanted_dids(&mut self, wanted_dids: HashSet<Did>) -> Result<(), Error> {
+    if wanted_dids.is_empty() {
+      return Err(Error::NoneWanted);
+    }
+    let options = json!({
+      "type": "options_update",
+      "payload": {
+        "wantedCollections": [POST_COLLECTION],
+        "wantedDids": wanted_dids.iter().map(|did| &**did).collect::<Vec<_>>(),
+      },
+    });
+    self.stream.send(Message::text(options.to_string())).await?;
+    self.wanted_dids = wanted_dids;
+    Ok(())
+  }
+
+  /// Waits for the next post created by, or identity or account change of, any of the wanted
+  /// users. Any other events are skipped.
+  ///
+  /// # Errors
+  ///
+  /// When the connection fails or is closed by the server.
+  pub async fn next_update(&mut self) -> Result<Update, Error> {
+    loop {
+      let text = match self.stream.next().await.ok_or(Error::Closed)?? {
+        Message::Text(text) => text,
+        Message::Close(_) => return Err(Error::Closed),
+        _ => continue, // Pings are answered by tungstenite itself
+      };
+
+      if let Some(update) = self.parse_update(&text) {
+        return Ok(update);
+      }
+    }
+  }
+
+  /// Waits for the next post created by any of the wanted users.
+  /// Any other events, identity and account changes included, are skipped.
+  ///
+  /// # Errors
+  ///
+  /// When the connection fails or is closed by the server.
+  pub async fn next_post(&mut self) -> Result<PostCreated, Error> {
+    loop {
+      if let Update::Post(post) = self.next_update().await? {
+        return Ok(post);
+      }
+    }
+  }
+
+  /// Parses an event, returning `Some` only if it's a post created by, or an identity or
+  /// account change of, a wanted user.
+  fn parse_update(&self, text: &str) -> Option<Update> {
+    let Event { did, time_us, kind } = serde_json::from_str(text)
+      .map_err(|e| {
+        event!(
+          Level::WARN,
+          "(Notice) Received invalid Jetstream event: {e}"
+        );
+      })
+      .ok()?;
+
+    match kind {
+      Kind::Commit { commit } => self.parse_post(&did, time_us, commit).map(Update::Post),
+      Kind::Identity {
+        identity: Identity { handle },
+      } => {
+        let did = self.wanted_dids.get(did.as_str())?.clone();
+        Some(Update::IdentityChanged {
+          did,
+          handle,
+          time_us,
+        })
+      }
+      Kind::Account {
+        account: Account { active, status },
+      } => {
+        let did = self.wanted_dids.get(did.as_str())?.clone();
+        let status = match (active, status) {
+          (true, _) => AccountStatus::Active,
+          (false, Some(status)) if status == "deleted" => AccountStatus::Deleted,
+          (false, status) => AccountStatus::Unavailable(status),
+        };
+        Some(Update::AccountChanged {
+          did,
+          status,
+          time_us,
+        })
+      }
+      Kind::Other => None,
+    }
+  }
+
+  /// Parses a commit, returning `Some` only if it's a post created by a wanted user.
+  fn parse_post(&self, did: &str, time_us: u64, commit: Commit) -> Option<PostCreated> {
+    let Commit {
+      operation: Operation::Create,
+      collection,
+      rkey,
+      cid: Some(cid),
+      record: Some(record),
+    } = commit
+    else {
+      return None;
+    };
+    if collection != POST_COLLECTION {
+      return None;
+    }
+    let did = self.wanted_dids.get(did)?.clone();
+
+    let record = serde_json::from_value(record)
+      .map_err(|e| {
+        event!(
+          Level::WARN,
+          "(Notice) Received invalid post record from {did}: {e}"
+        );
+      })
+      .ok()?;
+
+    Some(PostCreated {
+      did,
+      rkey,
+      cid,
+      time_us,
+      record,
+    })
+  }
+}
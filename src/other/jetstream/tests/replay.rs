@@ -0,0 +1,191 @@
+//! Replays recorded Jetstream events through a local WebSocket stand-in.
+
+use std::{collections::HashSet, sync::Arc};
+
+use futures_util::{SinkExt, StreamExt};
+use jetstream::{AccountStatus, Error, Subscription, Update};
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+use utils::Did;
+
+static RECORDED_EVENTS: &str = include_str!("fixtures/recorded_events.jsonl");
+
+/// Starts a stand-in Jetstream server that waits for the subscription's options update,
+/// replays every recorded event and then closes the connection.
+///
+/// # Returns
+/// The URL of the server and a handle resolving to the options update received.
+async fn stand_in() -> (String, tokio::task::JoinHandle<serde_json::Value>) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("ws://{}/subscribe", listener.local_addr().unwrap());
+
+  let server = tokio::spawn(async move {
+    let (tcp, _) = listener.accept().await.unwrap();
+    let mut ws = accept_async(tcp).await.unwrap();
+
+    let hello = match ws.next().await.unwrap().unwrap() {
+      Message::Text(text) => serde_json::from_str(&text).unwrap(),
+      other => panic!("Expected an options update, got {other:?}"),
+    };
+
+    for event in RECORDED_EVENTS.lines() {
+      ws.send(Message::text(event)).await.unwrap();
+    }
+    ws.close(None).await.unwrap();
+    hello
+  });
+
+  (url, server)
+}
+
+fn did(did: &str) -> Did {
+  Arc::from(did)
+}
+
+#[tokio::test]
+async fn yields_only_posts_created_by_wanted_users() {
+  let (url, server) = stand_in().await;
+  let alice = did("did:plc:watchedalice000000000000");
+  let bob = did("did:plc:watchedbob00000000000000");
+
+  let mut subscription =
+    Subscription::connect(&url, HashSet::from([alice.clone(), bob.clone()]), None)
+      .await
+      .unwrap();
+
+  let first = subscription.next_post().await.unwrap();
+  assert_eq!(first.did, alice);
+  assert_eq!(first.rkey, "3l3qo2vuowo2b");
+  assert_eq!(first.record.text, "Hello world!");
+  assert!(!first.is_reply());
+  assert_eq!(
+    first.uri(),
+    "at://did:plc:watchedalice000000000000/app.bsky.feed.post/3l3qo2vuowo2b"
+  );
+
+  let second = subscription.next_post().await.unwrap();
+  assert_eq!(second.did, bob);
+  assert_eq!(second.time_us, 1_726_000_000_000_006);
+  assert!(
+    !second.is_reply(),
+    "Replies within the author's own thread are posts"
+  );
+
+  let third = subscription.next_post().await.unwrap();
+  assert_eq!(third.did, alice);
+  assert!(third.is_reply());
+
+  assert!(matches!(subscription.next_post().await, Err(Error::Closed)));
+
+  let hello = server.await.unwrap();
+  assert_eq!(hello["type"], "options_update");
+  assert_eq!(
+    hello["payload"]["wantedCollections"][0],
+    "app.bsky.feed.post"
+  );
+  let wanted: HashSet<_> = hello["payload"]["wantedDids"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|d| d.as_str().unwrap())
+    .collect();
+  assert_eq!(wanted, HashSet::from([&*alice, &*bob]));
+}
+
+#[tokio::test]
+async fn refuses_to_subscribe_to_no_one() {
+  // Nothing listens there, so this would fail differently if it tried to connect
+  let connected = Subscription::connect("ws://127.0.0.1:1/subscribe", HashSet::new(), None).await;
+  assert!(matches!(connected, Err(Error::NoneWanted)));
+
+  let (url, server) = stand_in().await;
+  let alice = did("did:plc:watchedalice000000000000");
+  let mut subscription = Subscription::connect(&url, HashSet::from([alice.clone()]), None)
+    .await
+    .unwrap();
+  assert!(matches!(
+    subscription.update_wanted_dids(HashSet::new()).await,
+    Err(Error::NoneWanted)
+  ));
+
+  let post = subscription.next_post().await.unwrap();
+  assert_eq!(post.did, alice);
+
+  let hello = server.await.unwrap();
+  assert_eq!(hello["payload"]["wantedDids"][0], *alice);
+}
+
+#[tokio::test]
+async fn yields_identity_changes_of_wanted_users() {
+  let (url, server) = stand_in().await;
+  let bob = did("did:plc:watchedbob00000000000000");
+
+  let mut subscription = Subscription::connect(&url, HashSet::from([bob.clone()]), None)
+    .await
+    .unwrap();
+
+  let Update::IdentityChanged {
+    did: changed,
+    handle,
+    time_us,
+  } = subscription.next_update().await.unwrap()
+  else {
+    panic!("Expected bob's identity change first");
+  };
+  assert_eq!(changed, bob);
+  assert_eq!(handle.as_deref(), Some("bob.bsky.social"));
+  assert_eq!(time_us, 1_726_000_000_000_005);
+
+  let Update::Post(post) = subscription.next_update().await.unwrap() else {
+    panic!("Expected bob's post after their identity change");
+  };
+  assert_eq!(post.did, bob);
+
+  assert!(matches!(
+    subscription.next_update().await,
+    Err(Error::Closed)
+  ));
+
+  server.await.unwrap();
+}
+
+#[tokio::test]
+async fn yields_account_changes_of_wanted_users() {
+  let (url, server) = stand_in().await;
+  let alice = did("did:plc:watchedalice000000000000");
+  let someone = did("did:plc:someoneelse0000000000000");
+
+  let mut subscription =
+    Subscription::connect(&url, HashSet::from([alice.clone(), someone.clone()]), None)
+      .await
+      .unwrap();
+
+  let mut changes = Vec::new();
+  loop {
+    let update = subscription.next_update().await;
+    match update {
+      Ok(Update::AccountChanged {
+        did,
+        status,
+        time_us,
+      }) => changes.push((did, status, time_us)),
+      Ok(_) => {}
+      Err(Error::Closed) => break,
+      Err(e) => panic!("Unexpected error: {e}"),
+    }
+  }
+  assert_eq!(
+    changes,
+    [
+      (someone, AccountStatus::Deleted, 1_726_000_000_000_008),
+      (
+        alice.clone(),
+        AccountStatus::Unavailable(Some("deactivated".to_string())),
+        1_726_000_000_000_009
+      ),
+      (alice, AccountStatus::Active, 1_726_000_000_000_010),
+    ]
+  );
+
+  server.await.unwrap();
+}
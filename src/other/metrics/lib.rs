@@ -0,0 +1,129 @@
+//! The metrics of the bot, in the Prometheus format. Every metric is registered in a single
+//! registry, which is exposed by the optional HTTP server of the app.
+
+use lazy_static::lazy_static;
+use prometheus::{
+  Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+  Opts, Registry, TextEncoder,
+};
+
+lazy_static! {
+  /// Every metric is prefixed with `watcher_`.
+  static ref REGISTRY: Registry = new_registry();
+
+  /// Requests to Bluesky, by endpoint and outcome: `ok`, `api`, `bsky_bug` or `handled`,
+  /// for errors handled by the caller.
+  pub static ref BSKY_REQUESTS: IntCounterVec = register(IntCounterVec::new(
+    Opts::new("bsky_requests_total", "Requests to Bluesky, by endpoint and outcome."),
+    &["endpoint", "outcome"],
+  ));
+  /// How long requests to Bluesky took, by endpoint, including retries.
+  pub static ref BSKY_REQUEST_DURATION: HistogramVec = register(HistogramVec::new(
+    HistogramOpts::new(
+      "bsky_request_duration_seconds",
+      "How long requests to Bluesky took, by endpoint, including retries.",
+    ),
+    &["endpoint"],
+  ));
+  /// Retried attempts at requests to Bluesky, by endpoint and reason: `error`, `rate_limited`
+  /// or `session_expired`.
+  pub static ref BSKY_RETRIES: IntCounterVec = register(IntCounterVec::new(
+    Opts::new(
+      "bsky_retries_total",
+      "Retried attempts at requests to Bluesky, by endpoint and reason.",
+    ),
+    &["endpoint", "reason"],
+  ));
+  /// Requests to Bluesky currently in flight, by access: `read` or `write`.
+  pub static ref BSKY_IN_FLIGHT: IntGaugeVec = register(IntGaugeVec::new(
+    Opts::new("bsky_in_flight", "Requests to Bluesky currently in flight, by access."),
+    &["access"],
+  ));
+  /// Requests to Bluesky currently waiting for their turn, by access.
+  pub static ref BSKY_QUEUE_WAITING: IntGaugeVec = register(IntGaugeVec::new(
+    Opts::new(
+      "bsky_queue_waiting",
+      "Requests to Bluesky currently waiting for their turn, by access.",
+    ),
+    &["access"],
+  ));
+  /// Requests to Bluesky that had to wait for their turn, by access.
+  pub static ref BSKY_QUEUED: IntCounterVec = register(IntCounterVec::new(
+    Opts::new(
+      "bsky_queued_total",
+      "Requests to Bluesky that had to wait for their turn, by access.",
+    ),
+    &["access"],
+  ));
+  /// Times the bot logged in again after its session expired.
+  pub static ref BSKY_RELOGINS: IntCounter = register(IntCounter::new(
+    "bsky_relogins_total",
+    "Times the bot logged in again after its session expired.",
+  ));
+
+  /// Commands processed, by command and outcome: `ok` or `error`.
+  pub static ref COMMANDS: IntCounterVec = register(IntCounterVec::new(
+    Opts::new("commands_total", "Commands processed, by command and outcome."),
+    &["command", "outcome"],
+  ));
+
+  /// Notifications delivered to watchers, by outcome: `sent`, `failed` (to be retried) or
+  /// `dead` (given up on).
+  pub static ref NOTIFICATIONS: IntCounterVec = register(IntCounterVec::new(
+    Opts::new("notifications_total", "Notifications delivered to watchers, by outcome."),
+    &["outcome"],
+  ));
+
+  /// Users currently being watched.
+  pub static ref WATCHED_USERS: IntGauge = register(IntGauge::new(
+    "watched_users",
+    "Users currently being watched.",
+  ));
+  /// Users currently watching someone.
+  pub static ref WATCHERS: IntGauge = register(IntGauge::new(
+    "watchers",
+    "Users currently watching someone.",
+  ));
+  /// How late the latest poll of each watched user started, in `polling` mode.
+  pub static ref POLL_LAG: GaugeVec = register(GaugeVec::new(
+    Opts::new("poll_lag_seconds", "How late the latest poll of each watched user started."),
+    &["did"],
+  ));
+}
+
+#[expect(clippy::unwrap_used)] // Only fails on an invalid prefix, which is constant
+fn new_registry() -> Registry {
+  Registry::new_custom(Some("watcher".to_string()), None).unwrap()
+}
+
+/// Registers a metric, returning it back.
+#[expect(clippy::unwrap_used)] // Only fails on invalid or duplicate metrics, which are constant
+fn register<M: prometheus::core::Collector + Clone + 'static>(metric: prometheus::Result<M>) -> M {
+  let metric = metric.unwrap();
+  REGISTRY.register(Box::new(metric.clone())).unwrap();
+  metric
+}
+
+/// Encodes all the metrics in the Prometheus text format.
+#[must_use]
+pub fn encode() -> String {
+  // Metrics are only registered once first used, but should be exported regardless
+  lazy_static::initialize(&BSKY_REQUESTS);
+  lazy_static::initialize(&BSKY_REQUEST_DURATION);
+  lazy_static::initialize(&BSKY_RETRIES);
+  lazy_static::initialize(&BSKY_IN_FLIGHT);
+  lazy_static::initialize(&BSKY_QUEUE_WAITING);
+  lazy_static::initialize(&BSKY_QUEUED);
+  lazy_static::initialize(&BSKY_RELOGINS);
+  lazy_static::initialize(&COMMANDS);
+  lazy_static::initialize(&NOTIFICATIONS);
+  lazy_static::initialize(&WATCHED_USERS);
+  lazy_static::initialize(&WATCHERS);
+  lazy_static::initialize(&POLL_LAG);
+
+  let mut buf = Vec::new();
+  if let Err(e) = TextEncoder::new().encode(&REGISTRY.gather(), &mut buf) {
+    return format!("# Failed to encode metrics: {e}\n");
+  }
+  String::from_utf8(buf).unwrap_or_default()
+}
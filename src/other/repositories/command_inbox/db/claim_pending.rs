@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{command_inbox::InboxCommand, SqliteTransaction};
+
+/// Claims the pending commands of every convo that has none claimed by `now`, until
+/// `lease_until`, returning them from oldest to newest.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn claim_pending(
+  tx: &mut SqliteTransaction,
+  now: DateTime<Utc>,
+  lease_until: DateTime<Utc>,
+) -> sqlx::Result<Vec<InboxCommand>> {
+  let mut pending = sqlx::query!(
+    r#"UPDATE "CommandInbox" SET claimed_until = $2
+       WHERE status = 'pending' AND convo_id NOT IN (
+         SELECT convo_id FROM "CommandInbox" WHERE status = 'pending' AND claimed_until > $1
+       )
+       RETURNING message_id AS "message_id!", convo_id, sender_did, text, facets,
+         sent_at AS "sent_at: DateTime<Utc>", attempts"#,
+    now,
+    lease_until
+  )
+  .fetch_all(&mut **tx)
+  .await?;
+  // Returned in no particular order
+  pending.sort_by_key(|c| c.sent_at);
+
+  Ok(
+    pending
+      .into_iter()
+      .map(|c| InboxCommand {
+        message_id: c.message_id,
+        convo_id: c.convo_id,
+        sender_did: Arc::from(c.sender_did),
+        text: c.text,
+        facets: c.facets,
+        sent_at: c.sent_at,
+        attempts: c.attempts,
+      })
+      .collect(),
+  )
+}
@@ -0,0 +1,19 @@
+use crate::SqliteTransaction;
+
+/// Counts the pending commands of a sender.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn count_pending(tx: &mut SqliteTransaction, sender_did: &str) -> sqlx::Result<i64> {
+  let count = sqlx::query!(
+    r#"SELECT COUNT(*) AS "count!: i64" FROM "CommandInbox"
+       WHERE sender_did = $1 AND status = 'pending'"#,
+    sender_did
+  )
+  .fetch_one(&mut **tx)
+  .await?
+  .count;
+
+  Ok(count)
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+
+use crate::{command_inbox::Status, SqliteTransaction};
+
+/// Marks a command as no longer pending.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn finish(
+  tx: &mut SqliteTransaction,
+  message_id: &str,
+  status: Status,
+  now: DateTime<Utc>,
+) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"UPDATE "CommandInbox" SET status = $2, finished_at = $3 WHERE message_id = $1"#,
+    message_id,
+    status,
+    now
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+  command_inbox::{InboxCommand, Status},
+  Loadable, SqliteTransaction,
+};
+
+/// Adds a command, unless one with the same message id exists.
+///
+/// # Returns
+/// `None` if the command already existed.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn insert(
+  tx: &mut SqliteTransaction,
+  command: &InboxCommand,
+  status: Status,
+  now: DateTime<Utc>,
+) -> Loadable<()> {
+  let sender_did = &*command.sender_did;
+  let rows = sqlx::query!(
+    r#"INSERT INTO "CommandInbox" (message_id, convo_id, sender_did, text, facets, sent_at, status, received_at, finished_at)
+       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CASE WHEN $7 = 'pending' THEN NULL ELSE $8 END)
+       ON CONFLICT (message_id) DO NOTHING"#,
+    command.message_id,
+    command.convo_id,
+    sender_did,
+    command.text,
+    command.facets,
+    command.sent_at,
+    status,
+    now
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
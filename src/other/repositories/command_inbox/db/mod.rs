@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+  command_inbox::{InboxCommand, Status},
+  AppTransaction, Database, Loadable, SqliteTransaction,
+};
+
+mod insert;
+pub use insert::insert;
+
+mod count_pending;
+pub use count_pending::count_pending;
+
+mod claim_pending;
+pub use claim_pending::claim_pending;
+
+mod release;
+pub use release::release;
+
+mod start_attempt;
+pub use start_attempt::start_attempt;
+
+mod finish;
+pub use finish::finish;
+
+mod prune;
+pub use prune::prune;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+/// The queries on the command inbox, run within a transaction.
+/// Implemented for the transactions of every storage backend.
+#[async_trait]
+pub trait CommandInboxDb: Send {
+  /// Adds a command, unless one with the same message id exists.
+  async fn insert(
+    &mut self,
+    command: &InboxCommand,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> Loadable<()>;
+  /// Counts the pending commands of a sender.
+  async fn count_pending(&mut self, sender_did: &str) -> sqlx::Result<i64>;
+  /// Claims the pending commands of every convo that has none claimed by `now`, until
+  /// `lease_until`, returning them from oldest to newest.
+  async fn claim_pending(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+  ) -> sqlx::Result<Vec<InboxCommand>>;
+  /// Releases the claim on a command.
+  async fn release(&mut self, message_id: &str) -> sqlx::Result<()>;
+  /// Counts an attempt at processing a command, returning how many were made so far.
+  async fn start_attempt(&mut self, message_id: &str) -> sqlx::Result<i64>;
+  /// Marks a command as no longer pending.
+  async fn finish(
+    &mut self,
+    message_id: &str,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()>;
+  /// Removes the commands that finished before `before`.
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64>;
+  /// Commits the transaction.
+  async fn commit(self: Box<Self>) -> sqlx::Result<()>;
+}
+
+/// Starts a transaction on the current storage backend.
+///
+/// # Errors
+///
+/// Fails when a transaction cannot be started.
+pub async fn begin() -> sqlx::Result<Box<dyn CommandInboxDb>> {
+  Ok(match Database::get_tx().await? {
+    AppTransaction::Sqlite(tx) => Box::new(tx),
+    #[cfg(feature = "postgres")]
+    AppTransaction::Postgres(tx) => tx,
+  })
+}
+
+#[async_trait]
+impl CommandInboxDb for SqliteTransaction {
+  async fn insert(
+    &mut self,
+    command: &InboxCommand,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> Loadable<()> {
+    insert(self, command, status, now).await
+  }
+
+  async fn count_pending(&mut self, sender_did: &str) -> sqlx::Result<i64> {
+    count_pending(self, sender_did).await
+  }
+
+  async fn claim_pending(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+  ) -> sqlx::Result<Vec<InboxCommand>> {
+    claim_pending(self, now, lease_until).await
+  }
+
+  async fn release(&mut self, message_id: &str) -> sqlx::Result<()> {
+    release(self, message_id).await
+  }
+
+  async fn start_attempt(&mut self, message_id: &str) -> sqlx::Result<i64> {
+    start_attempt(self, message_id).await
+  }
+
+  async fn finish(
+    &mut self,
+    message_id: &str,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    finish(self, message_id, status, now).await
+  }
+
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64> {
+    prune(self, before).await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
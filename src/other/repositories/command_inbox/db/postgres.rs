@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::CommandInboxDb;
+use crate::{
+  command_inbox::{InboxCommand, Status},
+  Loadable, PgTransaction,
+};
+
+/// Statuses are stored as plain text, rather than as a Postgres enum.
+const fn status_to_text(status: Status) -> &'static str {
+  match status {
+    Status::Pending => "pending",
+    Status::Done => "done",
+    Status::Rejected => "rejected",
+    Status::Failed => "failed",
+  }
+}
+
+#[async_trait]
+impl CommandInboxDb for PgTransaction {
+  async fn insert(
+    &mut self,
+    command: &InboxCommand,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"INSERT INTO "CommandInbox" (message_id, convo_id, sender_did, text, facets, sent_at, status, received_at, finished_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CASE WHEN $7 = 'pending' THEN NULL ELSE $8 END)
+         ON CONFLICT (message_id) DO NOTHING"#,
+    )
+    .bind(&command.message_id)
+    .bind(&command.convo_id)
+    .bind(&*command.sender_did)
+    .bind(&command.text)
+    .bind(&command.facets)
+    .bind(command.sent_at)
+    .bind(status_to_text(status))
+    .bind(now)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn count_pending(&mut self, sender_did: &str) -> sqlx::Result<i64> {
+    sqlx::query_scalar(
+      r#"SELECT COUNT(*) FROM "CommandInbox" WHERE sender_did = $1 AND status = 'pending'"#,
+    )
+    .bind(sender_did)
+    .fetch_one(&mut **self)
+    .await
+  }
+
+  async fn claim_pending(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+  ) -> sqlx::Result<Vec<InboxCommand>> {
+    // Claims are taken one instance at a time, since skipping locked rows instead could have
+    // the newer commands of a convo claimed without the older ones
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('CommandInbox'))")
+      .execute(&mut **self)
+      .await?;
+    let mut pending: Vec<(
+      String,
+      String,
+      String,
+      String,
+      Option<String>,
+      DateTime<Utc>,
+      i64,
+    )> = sqlx::query_as(
+      r#"UPDATE "CommandInbox" SET claimed_until = $2
+         WHERE status = 'pending' AND convo_id NOT IN (
+           SELECT convo_id FROM "CommandInbox" WHERE status = 'pending' AND claimed_until > $1
+         )
+         RETURNING message_id, convo_id, sender_did, text, facets, sent_at, attempts"#,
+    )
+    .bind(now)
+    .bind(lease_until)
+    .fetch_all(&mut **self)
+    .await?;
+    // Returned in no particular order
+    pending.sort_by_key(|c| c.5);
+
+    Ok(
+      pending
+        .into_iter()
+        .map(
+          |(message_id, convo_id, sender_did, text, facets, sent_at, attempts)| InboxCommand {
+            message_id,
+            convo_id,
+            sender_did: Arc::from(sender_did),
+            text,
+            facets,
+            sent_at,
+            attempts,
+          },
+        )
+        .collect(),
+    )
+  }
+
+  async fn release(&mut self, message_id: &str) -> sqlx::Result<()> {
+    sqlx::query(r#"UPDATE "CommandInbox" SET claimed_until = NULL WHERE message_id = $1"#)
+      .bind(message_id)
+      .execute(&mut **self)
+      .await?;
+
+    Ok(())
+  }
+
+  async fn start_attempt(&mut self, message_id: &str) -> sqlx::Result<i64> {
+    sqlx::query_scalar(
+      r#"UPDATE "CommandInbox" SET attempts = attempts + 1 WHERE message_id = $1
+         RETURNING attempts"#,
+    )
+    .bind(message_id)
+    .fetch_one(&mut **self)
+    .await
+  }
+
+  async fn finish(
+    &mut self,
+    message_id: &str,
+    status: Status,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    sqlx::query(r#"UPDATE "CommandInbox" SET status = $2, finished_at = $3 WHERE message_id = $1"#)
+      .bind(message_id)
+      .bind(status_to_text(status))
+      .bind(now)
+      .execute(&mut **self)
+      .await?;
+
+    Ok(())
+  }
+
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64> {
+    let rows =
+      sqlx::query(r#"DELETE FROM "CommandInbox" WHERE status != 'pending' AND finished_at < $1"#)
+        .bind(before)
+        .execute(&mut **self)
+        .await?
+        .rows_affected();
+
+    Ok(rows)
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
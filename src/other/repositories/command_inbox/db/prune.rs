@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+
+use crate::SqliteTransaction;
+
+/// Removes the commands that finished before `before`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn prune(tx: &mut SqliteTransaction, before: DateTime<Utc>) -> sqlx::Result<u64> {
+  let rows = sqlx::query!(
+    r#"DELETE FROM "CommandInbox" WHERE status != 'pending' AND finished_at < $1"#,
+    before
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(rows)
+}
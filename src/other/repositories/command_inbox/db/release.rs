@@ -0,0 +1,17 @@
+use crate::SqliteTransaction;
+
+/// Releases the claim on a command, so that it can be picked up again right away.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn release(tx: &mut SqliteTransaction, message_id: &str) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"UPDATE "CommandInbox" SET claimed_until = NULL WHERE message_id = $1"#,
+    message_id
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::SqliteTransaction;
+
+/// Counts an attempt at processing a command, returning how many were made so far.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn start_attempt(tx: &mut SqliteTransaction, message_id: &str) -> sqlx::Result<i64> {
+  let attempts = sqlx::query!(
+    r#"UPDATE "CommandInbox" SET attempts = attempts + 1 WHERE message_id = $1
+       RETURNING attempts"#,
+    message_id
+  )
+  .fetch_one(&mut **tx)
+  .await?
+  .attempts;
+
+  Ok(attempts)
+}
@@ -0,0 +1,149 @@
+//! This module contains all the re-exported interfaces for manipulating the
+//! database of commands received by the bot, waiting to be processed.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{event, Level};
+use utils::Did;
+
+mod db;
+
+/// A command received through a chat message.
+#[derive(Debug, Clone)]
+pub struct InboxCommand {
+  /// The id of the chat message, which identifies the command.
+  pub message_id: String,
+  pub convo_id: String,
+  pub sender_did: Did,
+  pub text: String,
+  /// The rich text facets of the message, as JSON.
+  pub facets: Option<String>,
+  pub sent_at: DateTime<Utc>,
+  /// How many times processing the command was attempted so far.
+  pub attempts: i64,
+}
+
+/// Where a command is at in the inbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Status {
+  /// Waiting to be processed, or being processed if claimed.
+  Pending,
+  /// Processed, and answered if possible.
+  Done,
+  /// Skipped because the sender had too many pending commands when it was received.
+  Rejected,
+  /// Skipped after failing to be processed too many times.
+  Failed,
+}
+
+/// The outcome of receiving a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Received {
+  /// The command was queued for processing.
+  Queued,
+  /// The command was already received before.
+  Duplicate,
+  /// The sender already had `max_pending` commands pending, so the command was rejected.
+  Rejected,
+}
+
+/// Adds a received command to the inbox, unless it was already received before. Commands are
+/// queued as pending, unless their sender already has `max_pending` pending commands.
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is changed.
+pub async fn receive(command: &InboxCommand, max_pending: i64) -> sqlx::Result<Received> {
+  let mut tx = db::begin().await?;
+  let now = Utc::now();
+  // Written to before being read, so that the transaction holds the write lock from the start.
+  // Sqlite can't upgrade a transaction that has only read so far while others are writing.
+  if tx.insert(command, Status::Pending, now).await?.is_none() {
+    tx.commit().await?;
+    return Ok(Received::Duplicate);
+  }
+  // The command itself is counted as well
+  let received = if tx.count_pending(&command.sender_did).await? <= max_pending {
+    Received::Queued
+  } else {
+    tx.finish(&command.message_id, Status::Rejected, now)
+      .await?;
+    Received::Rejected
+  };
+  tx.commit().await?;
+  Ok(received)
+}
+
+/// Claims all pending commands, from oldest to newest, so that no other instance picks them up.
+///
+/// Commands from the same convo must be processed in this order, so convos that already have
+/// claimed commands are left out entirely. Claims last for `lease`, after which the commands are
+/// picked up again if they weren't processed nor released by then, e.g. after a crash.
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is claimed.
+pub async fn claim_pending(lease: TimeDelta) -> sqlx::Result<Vec<InboxCommand>> {
+  let mut tx = db::begin().await?;
+  let now = Utc::now();
+  let res = tx.claim_pending(now, now + lease).await?;
+  tx.commit().await?;
+  Ok(res)
+}
+
+/// Releases the claims on commands, so that the pending ones are picked up again right away.
+pub async fn release(message_ids: &[String]) {
+  let _ = async move {
+    let mut tx = db::begin().await?;
+    for message_id in message_ids {
+      tx.release(message_id).await?;
+    }
+    tx.commit().await
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to release claimed commands in the database. They'll be retried later: {e}"
+    );
+  });
+}
+
+/// Counts an attempt at processing a command, before it's made.
+///
+/// # Returns
+/// How many attempts were made so far, including this one.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn start_attempt(message_id: &str) -> sqlx::Result<i64> {
+  let mut tx = db::begin().await?;
+  let res = tx.start_attempt(message_id).await;
+  tx.commit().await?;
+  res
+}
+
+/// Marks a command as no longer pending.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn finish(message_id: &str, status: Status) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.finish(message_id, status, Utc::now()).await?;
+  tx.commit().await
+}
+
+/// Removes the commands that finished before `before`, after which receiving them again would
+/// have them processed again. Returns how many were removed.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn prune(before: DateTime<Utc>) -> sqlx::Result<u64> {
+  let mut tx = db::begin().await?;
+  let res = tx.prune(before).await;
+  tx.commit().await?;
+  res
+}
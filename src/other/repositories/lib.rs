@@ -1,9 +1,15 @@
+pub mod command_inbox;
+pub mod notification_outbox;
+pub mod post_activity;
 pub mod watched_user;
+pub mod watcher_settings;
 
 use async_once::AsyncOnce;
 use lazy_static::lazy_static;
-use sqlx::migrate::MigrateDatabase;
+use sqlx::migrate::{MigrateDatabase, MigrateError, Migrator};
 use sqlx::sqlite::SqlitePoolOptions;
+#[cfg(feature = "postgres")]
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres};
 use sqlx::{Error, Sqlite, SqlitePool, Transaction};
 use std::time::Duration;
 use tracing::{event, Level};
@@ -23,7 +29,57 @@ pub(crate) type Loadable<T> = sqlx::Result<Option<T>>;
 
 /// # Transaction
 /// Represents an Sqlite transaction
-pub(crate) type AppTransaction = Transaction<'static, Sqlite>;
+pub(crate) type SqliteTransaction = Transaction<'static, Sqlite>;
+
+/// # Transaction
+/// Represents a Postgres transaction
+#[cfg(feature = "postgres")]
+pub(crate) type PgTransaction = Transaction<'static, Postgres>;
+
+/// # Transaction
+/// Represents a transaction on whichever backend the database is stored in.
+/// Each repository exposes its queries through a trait implemented for every
+/// backend's transaction, so this is usually turned into one of those.
+pub(crate) enum AppTransaction {
+  Sqlite(SqliteTransaction),
+  #[cfg(feature = "postgres")]
+  Postgres(Box<PgTransaction>),
+}
+
+/// The storage backends the database can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+  Sqlite,
+  #[cfg(feature = "postgres")]
+  Postgres,
+}
+impl Backend {
+  /// Picks the backend from the scheme of the database URL.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the URL points to Postgres but the `postgres` feature is disabled.
+  fn from_url(db_url: &str) -> Self {
+    let is_postgres = db_url.starts_with("postgres://") || db_url.starts_with("postgresql://");
+    #[cfg(feature = "postgres")]
+    if is_postgres {
+      return Self::Postgres;
+    }
+    #[cfg(not(feature = "postgres"))]
+    assert!(
+      !is_postgres,
+      "DATABASE_URL points to Postgres, but the `postgres` feature is disabled!"
+    );
+    Self::Sqlite
+  }
+}
+
+/// The connection pool of one of the storage backends.
+enum Pool {
+  Sqlite(SqlitePool),
+  #[cfg(feature = "postgres")]
+  Postgres(PgPool),
+}
 
 lazy_static! {
   /// Current DB connection pool
@@ -32,43 +88,67 @@ lazy_static! {
 
 /// The database connection pool.
 pub struct Database {
-  pool: SqlitePool,
+  pool: Pool,
 }
 impl Database {
-  /// Initiallizes by checking if the database file exists, creating it if it
+  /// Initiallizes by checking if the database exists, creating it if it
   /// doesn't, and then connecting to it, initializing the connection pool.
+  /// The backend is picked from the scheme of `DATABASE_URL`.
   ///
   /// # Panics
   ///
   /// Panics when connection pool fails to initialize.
-  #[expect(clippy::cognitive_complexity)]
   async fn init() -> Self {
     let db_url = owned_var_or_else("DATABASE_URL", || String::from("sqlite://data.db"));
-
-    if Sqlite::database_exists(&db_url).await.unwrap_or(false) {
-      event!(Level::INFO, "Database found: {}", &db_url);
-    } else {
-      event!(Level::INFO, "Creating database: {}", &db_url);
-      match Sqlite::create_database(&db_url).await {
-        Ok(()) => event!(Level::DEBUG, "Successfully created new DB file!"),
-        Err(e) => panic!("Failed to create db! Error: {e}"),
-      }
-    }
-
     let conn_pool_max: u32 = owned_var_or("DB_CONN_POOL_MAX", 100);
 
-    let pool = SqlitePoolOptions::new()
-      .max_connections(conn_pool_max)
-      .connect(&db_url)
-      .await
-      .unwrap_or_else(|e| panic!("Failed to connect to Sqlite DB! Error: {e}"));
+    let pool = match Backend::from_url(&db_url) {
+      Backend::Sqlite => {
+        ensure_exists::<Sqlite>(&db_url).await;
+        Pool::Sqlite(
+          SqlitePoolOptions::new()
+            .max_connections(conn_pool_max)
+            .connect(&db_url)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to connect to Sqlite DB! Error: {e}")),
+        )
+      }
+      #[cfg(feature = "postgres")]
+      Backend::Postgres => {
+        ensure_exists::<Postgres>(&db_url).await;
+        Pool::Postgres(
+          PgPoolOptions::new()
+            .max_connections(conn_pool_max)
+            .connect(&db_url)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to connect to Postgres DB! Error: {e}")),
+        )
+      }
+    };
 
     Self { pool }
   }
 
-  /// Method to get a reference to the database connection pool.
-  pub async fn get_pool() -> &'static SqlitePool {
-    &DB.get().await.pool
+  /// Method to get the backend the database is stored in.
+  pub async fn backend() -> Backend {
+    match DB.get().await.pool {
+      Pool::Sqlite(_) => Backend::Sqlite,
+      #[cfg(feature = "postgres")]
+      Pool::Postgres(_) => Backend::Postgres,
+    }
+  }
+
+  /// Method to run the migrations meant for the current backend.
+  ///
+  /// # Errors
+  ///
+  /// Fails when any of the migrations fail to apply.
+  pub async fn migrate(migrator: &Migrator) -> Result<(), MigrateError> {
+    match &DB.get().await.pool {
+      Pool::Sqlite(pool) => migrator.run(pool).await,
+      #[cfg(feature = "postgres")]
+      Pool::Postgres(pool) => migrator.run(pool).await,
+    }
   }
 
   /// Method to get a transaction from the database connection pool.
@@ -77,7 +157,27 @@ impl Database {
   ///
   /// Fails when a transaction cannot be started.
   pub(crate) async fn get_tx() -> Result<AppTransaction, Error> {
-    DB.get().await.pool.begin().await
+    match &DB.get().await.pool {
+      Pool::Sqlite(pool) => pool.begin().await.map(AppTransaction::Sqlite),
+      #[cfg(feature = "postgres")]
+      Pool::Postgres(pool) => pool
+        .begin()
+        .await
+        .map(|tx| AppTransaction::Postgres(Box::new(tx))),
+    }
+  }
+
+  /// Method for checking whether the database can currently be reached.
+  pub async fn ping() -> bool {
+    let result = match &DB.get().await.pool {
+      Pool::Sqlite(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+      #[cfg(feature = "postgres")]
+      Pool::Postgres(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+    };
+    if let Err(e) = &result {
+      event!(Level::WARN, "(Notice) Failed to reach the database: {e}");
+    }
+    result.is_ok()
   }
 
   /// Method for gracefully disconnecting from the database.
@@ -86,7 +186,11 @@ impl Database {
     let db_countdown = time::sleep(Duration::from_secs(15));
     let db_shutdown = async {
       event!(Level::INFO, "Closing database connections (max. 15s)...");
-      DB.get().await.pool.close().await;
+      match &DB.get().await.pool {
+        Pool::Sqlite(pool) => pool.close().await,
+        #[cfg(feature = "postgres")]
+        Pool::Postgres(pool) => pool.close().await,
+      }
       event!(Level::INFO, "Database connections closed!");
     };
 
@@ -96,3 +200,20 @@ impl Database {
     }
   }
 }
+
+/// Creates the database if it doesn't exist yet.
+///
+/// # Panics
+///
+/// Panics when the database fails to be created.
+async fn ensure_exists<DB: MigrateDatabase>(db_url: &str) {
+  if DB::database_exists(db_url).await.unwrap_or(false) {
+    event!(Level::INFO, "Database found: {}", db_url);
+  } else {
+    event!(Level::INFO, "Creating database: {}", db_url);
+    match DB::create_database(db_url).await {
+      Ok(()) => event!(Level::DEBUG, "Successfully created new DB!"),
+      Err(e) => panic!("Failed to create db! Error: {e}"),
+    }
+  }
+}
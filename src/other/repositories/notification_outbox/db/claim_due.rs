@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+  notification_outbox::{Kind, OutboxNotification, OutboxPost},
+  SqliteTransaction,
+};
+
+/// Claims up to `limit` pending notifications due by `now`, until `lease_until`, returning them
+/// from oldest to newest.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn claim_due(
+  tx: &mut SqliteTransaction,
+  now: DateTime<Utc>,
+  lease_until: DateTime<Utc>,
+  limit: i64,
+) -> sqlx::Result<Vec<OutboxNotification>> {
+  let mut due = sqlx::query!(
+    r#"UPDATE "NotificationOutbox" SET next_attempt_at = $2
+       WHERE id IN (
+         SELECT id FROM "NotificationOutbox"
+         WHERE status = 'pending' AND julianday(next_attempt_at) <= julianday($1)
+         ORDER BY id LIMIT $3
+       )
+       RETURNING
+         id AS "id!",
+         watcher_did,
+         watched_did,
+         kind AS "kind: Kind",
+         post_uri,
+         post_cid,
+         post_text,
+         is_reply AS "is_reply: bool",
+         previous_handle,
+         in_digest AS "in_digest: bool",
+         attempts"#,
+    now,
+    lease_until,
+    limit
+  )
+  .fetch_all(&mut **tx)
+  .await?;
+  // Returned in no particular order
+  due.sort_unstable_by_key(|n| n.id);
+
+  Ok(
+    due
+      .into_iter()
+      .map(|n| OutboxNotification {
+        id: n.id,
+        watcher_did: Arc::from(n.watcher_did),
+        watched_did: Arc::from(n.watched_did),
+        kind: n.kind,
+        post: n.post_uri.map(|uri| OutboxPost {
+          uri,
+          cid: n.post_cid.unwrap_or_default(),
+          text: n.post_text.unwrap_or_default(),
+          is_reply: n.is_reply,
+        }),
+        previous_handle: n.previous_handle,
+        in_digest: n.in_digest,
+        attempts: n.attempts,
+      })
+      .collect(),
+  )
+}
@@ -0,0 +1,17 @@
+use crate::SqliteTransaction;
+
+/// Counts the notifications that were given up on.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn count_dead(tx: &mut SqliteTransaction) -> sqlx::Result<i64> {
+  let count = sqlx::query!(
+    r#"SELECT COUNT(*) AS "count!: i64" FROM "NotificationOutbox" WHERE status = 'dead'"#
+  )
+  .fetch_one(&mut **tx)
+  .await?
+  .count;
+
+  Ok(count)
+}
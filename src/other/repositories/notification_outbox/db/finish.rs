@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+
+use crate::{notification_outbox::Status, SqliteTransaction};
+
+/// Marks a notification as no longer pending, counting a failed attempt if there's an error.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn finish(
+  tx: &mut SqliteTransaction,
+  id: i64,
+  status: Status,
+  error: Option<&str>,
+  now: DateTime<Utc>,
+) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"UPDATE "NotificationOutbox"
+       SET status = $2, finished_at = $3,
+         attempts = attempts + CASE WHEN $4 IS NULL THEN 0 ELSE 1 END,
+         last_error = COALESCE($4, last_error)
+       WHERE id = $1"#,
+    id,
+    status,
+    now,
+    error
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
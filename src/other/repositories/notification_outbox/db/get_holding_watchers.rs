@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Returns the watchers with notifications held for their next digest.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_holding_watchers(tx: &mut SqliteTransaction) -> sqlx::Result<Vec<Did>> {
+  let watchers =
+    sqlx::query!(r#"SELECT DISTINCT watcher_did FROM "NotificationOutbox" WHERE status = 'held'"#)
+      .fetch_all(&mut **tx)
+      .await?;
+
+  Ok(
+    watchers
+      .into_iter()
+      .map(|w| Arc::from(w.watcher_did))
+      .collect(),
+  )
+}
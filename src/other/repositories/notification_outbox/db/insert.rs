@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{
+  notification_outbox::{Kind, OutboxPost, Status},
+  Loadable, SqliteTransaction,
+};
+
+/// Adds a notification, unless the watcher was already notified about its post. Held
+/// notifications are only delivered in the watcher's next digest, once released.
+///
+/// # Returns
+/// `None` if the watcher was already notified about the post.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+#[expect(clippy::too_many_arguments)] // One per column
+pub async fn insert(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  watched_did: &Did,
+  kind: Kind,
+  post: Option<&OutboxPost>,
+  previous_handle: Option<&str>,
+  held: bool,
+  now: DateTime<Utc>,
+) -> Loadable<()> {
+  let watcher = &**watcher;
+  let author = &**watched_did;
+  let post_uri = post.map(|post| &post.uri);
+  let post_cid = post.map(|post| &post.cid);
+  let post_text = post.map(|post| &post.text);
+  let is_reply = post.is_some_and(|post| post.is_reply);
+  let status = if held { Status::Held } else { Status::Pending };
+  let rows = sqlx::query!(
+    r#"INSERT INTO "NotificationOutbox"
+         (watcher_did, watched_did, kind, post_uri, post_cid, post_text, is_reply, previous_handle, status, in_digest, next_attempt_at, created_at)
+       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+       ON CONFLICT (watcher_did, post_uri) DO NOTHING"#,
+    watcher,
+    author,
+    kind,
+    post_uri,
+    post_cid,
+    post_text,
+    is_reply,
+    previous_handle,
+    status,
+    held,
+    now
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
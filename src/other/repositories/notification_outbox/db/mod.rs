@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{
+  notification_outbox::{Kind, OutboxNotification, OutboxPost, Status},
+  AppTransaction, Database, Loadable, SqliteTransaction,
+};
+
+mod insert;
+pub use insert::insert;
+
+mod claim_due;
+pub use claim_due::claim_due;
+
+mod retry_at;
+pub use retry_at::retry_at;
+
+mod finish;
+pub use finish::finish;
+
+mod get_holding_watchers;
+pub use get_holding_watchers::get_holding_watchers;
+
+mod release_held;
+pub use release_held::release_held;
+
+mod count_dead;
+pub use count_dead::count_dead;
+
+mod prune;
+pub use prune::prune;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+/// The queries on the notification outbox, run within a transaction.
+/// Implemented for the transactions of every storage backend.
+#[async_trait]
+pub trait NotificationOutboxDb: Send {
+  /// Adds a notification, unless the watcher was already notified about its post. Held
+  /// notifications are only delivered in the watcher's next digest, once released.
+  #[expect(clippy::too_many_arguments)] // One per column
+  async fn insert(
+    &mut self,
+    watcher: &Did,
+    watched_did: &Did,
+    kind: Kind,
+    post: Option<&OutboxPost>,
+    previous_handle: Option<&str>,
+    held: bool,
+    now: DateTime<Utc>,
+  ) -> Loadable<()>;
+  /// Claims up to `limit` pending notifications due by `now`, until `lease_until`, returning them
+  /// from oldest to newest.
+  async fn claim_due(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+    limit: i64,
+  ) -> sqlx::Result<Vec<OutboxNotification>>;
+  /// Counts a failed attempt at delivering a notification, and schedules it to be retried.
+  async fn retry_at(
+    &mut self,
+    id: i64,
+    next_attempt_at: DateTime<Utc>,
+    error: &str,
+  ) -> sqlx::Result<()>;
+  /// Marks a notification as no longer pending, counting a failed attempt if there's an error.
+  async fn finish(
+    &mut self,
+    id: i64,
+    status: Status,
+    error: Option<&str>,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()>;
+  /// Returns the watchers with notifications held for their next digest.
+  async fn get_holding_watchers(&mut self) -> sqlx::Result<Vec<Did>>;
+  /// Makes the notifications held for a watcher's digest due by `now`, returning how many.
+  async fn release_held(&mut self, watcher: &Did, now: DateTime<Utc>) -> sqlx::Result<u64>;
+  /// Counts the notifications that were given up on.
+  async fn count_dead(&mut self) -> sqlx::Result<i64>;
+  /// Removes the notifications that were sent before `before`.
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64>;
+  /// Commits the transaction.
+  async fn commit(self: Box<Self>) -> sqlx::Result<()>;
+}
+
+/// Starts a transaction on the current storage backend.
+///
+/// # Errors
+///
+/// Fails when a transaction cannot be started.
+pub async fn begin() -> sqlx::Result<Box<dyn NotificationOutboxDb>> {
+  Ok(match Database::get_tx().await? {
+    AppTransaction::Sqlite(tx) => Box::new(tx),
+    #[cfg(feature = "postgres")]
+    AppTransaction::Postgres(tx) => tx,
+  })
+}
+
+#[async_trait]
+impl NotificationOutboxDb for SqliteTransaction {
+  async fn insert(
+    &mut self,
+    watcher: &Did,
+    watched_did: &Did,
+    kind: Kind,
+    post: Option<&OutboxPost>,
+    previous_handle: Option<&str>,
+    held: bool,
+    now: DateTime<Utc>,
+  ) -> Loadable<()> {
+    insert(
+      self,
+      watcher,
+      watched_did,
+      kind,
+      post,
+      previous_handle,
+      held,
+      now,
+    )
+    .await
+  }
+
+  async fn claim_due(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+    limit: i64,
+  ) -> sqlx::Result<Vec<OutboxNotification>> {
+    claim_due(self, now, lease_until, limit).await
+  }
+
+  async fn retry_at(
+    &mut self,
+    id: i64,
+    next_attempt_at: DateTime<Utc>,
+    error: &str,
+  ) -> sqlx::Result<()> {
+    retry_at(self, id, next_attempt_at, error).await
+  }
+
+  async fn finish(
+    &mut self,
+    id: i64,
+    status: Status,
+    error: Option<&str>,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    finish(self, id, status, error, now).await
+  }
+
+  async fn get_holding_watchers(&mut self) -> sqlx::Result<Vec<Did>> {
+    get_holding_watchers(self).await
+  }
+
+  async fn release_held(&mut self, watcher: &Did, now: DateTime<Utc>) -> sqlx::Result<u64> {
+    release_held(self, watcher, now).await
+  }
+
+  async fn count_dead(&mut self) -> sqlx::Result<i64> {
+    count_dead(self).await
+  }
+
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64> {
+    prune(self, before).await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
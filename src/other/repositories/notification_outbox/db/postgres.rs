@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::{event, Level};
+use utils::Did;
+
+use super::NotificationOutboxDb;
+use crate::{
+  notification_outbox::{Kind, OutboxNotification, OutboxPost, Status},
+  Loadable, PgTransaction,
+};
+
+type Row = (
+  i64,
+  String,
+  String,
+  String,
+  Option<String>,
+  Option<String>,
+  Option<String>,
+  bool,
+  Option<String>,
+  bool,
+  i64,
+);
+
+/// Kinds are stored as plain text, just like in Sqlite, rather than as a custom type.
+const fn kind_to_text(kind: Kind) -> &'static str {
+  match kind {
+    Kind::Post => "post",
+    Kind::OptedOut => "optedout",
+    Kind::Gone => "gone",
+    Kind::Renamed => "renamed",
+  }
+}
+
+fn kind_from_text(id: i64, kind: &str) -> Kind {
+  match kind {
+    "post" => Kind::Post,
+    "optedout" => Kind::OptedOut,
+    "gone" => Kind::Gone,
+    "renamed" => Kind::Renamed,
+    _ => {
+      event!(
+        Level::WARN,
+        "Invalid kind saved for notification {id}: {kind}"
+      );
+      Kind::Post
+    }
+  }
+}
+
+/// Statuses are stored as plain text, rather than as a Postgres enum.
+const fn status_to_text(status: Status) -> &'static str {
+  match status {
+    Status::Held => "held",
+    Status::Pending => "pending",
+    Status::Sent => "sent",
+    Status::Dead => "dead",
+  }
+}
+
+#[async_trait]
+impl NotificationOutboxDb for PgTransaction {
+  async fn insert(
+    &mut self,
+    watcher: &Did,
+    watched_did: &Did,
+    kind: Kind,
+    post: Option<&OutboxPost>,
+    previous_handle: Option<&str>,
+    held: bool,
+    now: DateTime<Utc>,
+  ) -> Loadable<()> {
+    let status = if held { Status::Held } else { Status::Pending };
+    let rows = sqlx::query(
+      r#"INSERT INTO "NotificationOutbox"
+           (watcher_did, watched_did, kind, post_uri, post_cid, post_text, is_reply, previous_handle, status, in_digest, next_attempt_at, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+         ON CONFLICT (watcher_did, post_uri) DO NOTHING"#,
+    )
+    .bind(&**watcher)
+    .bind(&**watched_did)
+    .bind(kind_to_text(kind))
+    .bind(post.map(|post| &post.uri))
+    .bind(post.map(|post| &post.cid))
+    .bind(post.map(|post| &post.text))
+    .bind(post.is_some_and(|post| post.is_reply))
+    .bind(previous_handle)
+    .bind(status_to_text(status))
+    .bind(held)
+    .bind(now)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn claim_due(
+    &mut self,
+    now: DateTime<Utc>,
+    lease_until: DateTime<Utc>,
+    limit: i64,
+  ) -> sqlx::Result<Vec<OutboxNotification>> {
+    // Rows claimed by other instances in the meantime are skipped rather than waited on
+    let mut due: Vec<Row> = sqlx::query_as(
+      r#"UPDATE "NotificationOutbox" SET next_attempt_at = $2
+         WHERE id IN (
+           SELECT id FROM "NotificationOutbox"
+           WHERE status = 'pending' AND next_attempt_at <= $1
+           ORDER BY id LIMIT $3
+           FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, watcher_did, watched_did, kind, post_uri, post_cid, post_text, is_reply, previous_handle, in_digest, attempts"#,
+    )
+    .bind(now)
+    .bind(lease_until)
+    .bind(limit)
+    .fetch_all(&mut **self)
+    .await?;
+    // Returned in no particular order
+    due.sort_unstable_by_key(|n| n.0);
+
+    Ok(
+      due
+        .into_iter()
+        .map(
+          |(
+            id,
+            watcher_did,
+            watched_did,
+            kind,
+            post_uri,
+            post_cid,
+            post_text,
+            is_reply,
+            previous_handle,
+            in_digest,
+            attempts,
+          )| {
+            OutboxNotification {
+              id,
+              watcher_did: Arc::from(watcher_did),
+              watched_did: Arc::from(watched_did),
+              kind: kind_from_text(id, &kind),
+              post: post_uri.map(|uri| OutboxPost {
+                uri,
+                cid: post_cid.unwrap_or_default(),
+                text: post_text.unwrap_or_default(),
+                is_reply,
+              }),
+              previous_handle,
+              in_digest,
+              attempts,
+            }
+          },
+        )
+        .collect(),
+    )
+  }
+
+  async fn retry_at(
+    &mut self,
+    id: i64,
+    next_attempt_at: DateTime<Utc>,
+    error: &str,
+  ) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"UPDATE "NotificationOutbox"
+         SET attempts = attempts + 1, next_attempt_at = $2, last_error = $3
+         WHERE id = $1"#,
+    )
+    .bind(id)
+    .bind(next_attempt_at)
+    .bind(error)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn finish(
+    &mut self,
+    id: i64,
+    status: Status,
+    error: Option<&str>,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"UPDATE "NotificationOutbox"
+         SET status = $2, finished_at = $3,
+           attempts = attempts + CASE WHEN $4::TEXT IS NULL THEN 0 ELSE 1 END,
+           last_error = COALESCE($4, last_error)
+         WHERE id = $1"#,
+    )
+    .bind(id)
+    .bind(status_to_text(status))
+    .bind(now)
+    .bind(error)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn get_holding_watchers(&mut self) -> sqlx::Result<Vec<Did>> {
+    let watchers: Vec<String> = sqlx::query_scalar(
+      r#"SELECT DISTINCT watcher_did FROM "NotificationOutbox" WHERE status = 'held'"#,
+    )
+    .fetch_all(&mut **self)
+    .await?;
+
+    Ok(watchers.into_iter().map(Arc::from).collect())
+  }
+
+  async fn release_held(&mut self, watcher: &Did, now: DateTime<Utc>) -> sqlx::Result<u64> {
+    let rows = sqlx::query(
+      r#"UPDATE "NotificationOutbox" SET status = 'pending', next_attempt_at = $2
+         WHERE watcher_did = $1 AND status = 'held'"#,
+    )
+    .bind(&**watcher)
+    .bind(now)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+  }
+
+  async fn count_dead(&mut self) -> sqlx::Result<i64> {
+    sqlx::query_scalar(r#"SELECT COUNT(*) FROM "NotificationOutbox" WHERE status = 'dead'"#)
+      .fetch_one(&mut **self)
+      .await
+  }
+
+  async fn prune(&mut self, before: DateTime<Utc>) -> sqlx::Result<u64> {
+    let rows =
+      sqlx::query(r#"DELETE FROM "NotificationOutbox" WHERE status = 'sent' AND finished_at < $1"#)
+        .bind(before)
+        .execute(&mut **self)
+        .await?
+        .rows_affected();
+
+    Ok(rows)
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
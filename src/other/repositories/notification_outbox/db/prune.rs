@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+
+use crate::SqliteTransaction;
+
+/// Removes the notifications that were sent before `before`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn prune(tx: &mut SqliteTransaction, before: DateTime<Utc>) -> sqlx::Result<u64> {
+  let rows = sqlx::query!(
+    r#"DELETE FROM "NotificationOutbox" WHERE status = 'sent' AND julianday(finished_at) < julianday($1)"#,
+    before
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(rows)
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Makes the notifications held for a watcher's digest due by `now`, returning how many.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn release_held(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  now: DateTime<Utc>,
+) -> sqlx::Result<u64> {
+  let did = &**watcher;
+  let rows = sqlx::query!(
+    r#"UPDATE "NotificationOutbox" SET status = 'pending', next_attempt_at = $2
+       WHERE watcher_did = $1 AND status = 'held'"#,
+    did,
+    now
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(rows)
+}
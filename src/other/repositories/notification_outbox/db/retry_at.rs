@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+
+use crate::SqliteTransaction;
+
+/// Counts a failed attempt at delivering a notification, and schedules it to be retried.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn retry_at(
+  tx: &mut SqliteTransaction,
+  id: i64,
+  next_attempt_at: DateTime<Utc>,
+  error: &str,
+) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"UPDATE "NotificationOutbox"
+       SET attempts = attempts + 1, next_attempt_at = $2, last_error = $3
+       WHERE id = $1"#,
+    id,
+    next_attempt_at,
+    error
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
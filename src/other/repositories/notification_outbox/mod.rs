@@ -0,0 +1,249 @@
+//! This module contains all the re-exported interfaces for manipulating the
+//! database of notifications waiting to be delivered to watchers, which are
+//! retried until they're either sent or given up on.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{event, Level};
+use utils::Did;
+
+mod db;
+
+/// What a notification is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Kind {
+  /// A new post or reply from the watched user.
+  Post,
+  /// The watched user has opted out of being watched.
+  OptedOut,
+  /// The watched user's account is gone, i.e. deleted or unreachable for too long.
+  Gone,
+  /// The watched user's handle has changed.
+  Renamed,
+}
+
+/// Where a notification is at in the outbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Status {
+  /// Waiting for the watcher's next digest, or for their quiet hours to end.
+  Held,
+  /// Waiting to be delivered, or to be retried.
+  Pending,
+  Sent,
+  /// Given up on after failing too many times. Kept for the operator to look into.
+  Dead,
+}
+
+/// A post that a watcher is to be notified about.
+#[derive(Debug, Clone)]
+pub struct OutboxPost {
+  pub uri: String,
+  pub cid: String,
+  pub text: String,
+  pub is_reply: bool,
+}
+
+/// A notification waiting in the outbox.
+#[derive(Debug, Clone)]
+pub struct OutboxNotification {
+  pub id: i64,
+  pub watcher_did: Did,
+  pub watched_did: Did,
+  pub kind: Kind,
+  /// Only set for `Kind::Post`.
+  pub post: Option<OutboxPost>,
+  /// Only set for `Kind::Renamed`.
+  pub previous_handle: Option<String>,
+  /// Whether the notification was held for a digest, which it's to be delivered in.
+  pub in_digest: bool,
+  /// How many times delivering the notification failed so far.
+  pub attempts: i64,
+}
+
+/// Adds the notifications about new posts from a watched user for a watcher to the outbox.
+///
+/// Posts that the watcher was already notified about are skipped. If `held`, they're only
+/// delivered in the watcher's next digest (check `release_held`).
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is added.
+pub async fn enqueue_posts(
+  watcher: &Did,
+  watched_did: &Did,
+  posts: &[OutboxPost],
+  held: bool,
+) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  let now = Utc::now();
+  for post in posts {
+    tx.insert(
+      watcher,
+      watched_did,
+      Kind::Post,
+      Some(post),
+      None,
+      held,
+      now,
+    )
+    .await?;
+  }
+  tx.commit().await
+}
+
+/// Adds a notification about a watched user that isn't about any post, e.g. that they opted
+/// out, to the outbox.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn enqueue_notice(watcher: &Did, watched_did: &Did, kind: Kind) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.insert(watcher, watched_did, kind, None, None, false, Utc::now())
+    .await?;
+  tx.commit().await
+}
+
+/// Adds a notification that a watched user's handle has changed from `previous_handle` to the
+/// outbox.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn enqueue_renamed(
+  watcher: &Did,
+  watched_did: &Did,
+  previous_handle: &str,
+) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.insert(
+    watcher,
+    watched_did,
+    Kind::Renamed,
+    None,
+    Some(previous_handle),
+    false,
+    Utc::now(),
+  )
+  .await?;
+  tx.commit().await
+}
+
+/// Claims up to `limit` pending notifications that are due, from oldest to newest, so that no
+/// other instance picks them up.
+///
+/// Their next attempt is pushed back by `lease`, so that they're
+/// picked up again if they're neither sent nor rescheduled by then, e.g. after a crash.
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is claimed.
+pub async fn claim_due(limit: i64, lease: TimeDelta) -> sqlx::Result<Vec<OutboxNotification>> {
+  let mut tx = db::begin().await?;
+  let now = Utc::now();
+  let res = tx.claim_due(now, now + lease, limit).await?;
+  tx.commit().await?;
+  Ok(res)
+}
+
+/// Returns a set of all watchers with notifications held for their next digest.
+pub async fn get_holding_watchers() -> HashSet<Did> {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get_holding_watchers().await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get watchers with held notifications from the database: {e}"
+    );
+  })
+  .map(|watchers| watchers.into_iter().collect())
+  .unwrap_or_default()
+}
+
+/// Releases the notifications held for a watcher's digest, so that they're delivered together
+/// right away. Returns how many were released.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn release_held(watcher: &Did) -> sqlx::Result<u64> {
+  let mut tx = db::begin().await?;
+  let res = tx.release_held(watcher, Utc::now()).await;
+  tx.commit().await?;
+  res
+}
+
+/// Marks notifications as sent.
+pub async fn mark_sent(ids: &[i64]) {
+  let _ = async move {
+    let mut tx = db::begin().await?;
+    let now = Utc::now();
+    for id in ids {
+      tx.finish(*id, Status::Sent, None, now).await?;
+    }
+    tx.commit().await
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to mark notifications as sent in the database. They might be sent again: {e}"
+    );
+  });
+}
+
+/// Counts a failed attempt at delivering a notification, and schedules it to be retried.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn retry_at(id: i64, next_attempt_at: DateTime<Utc>, error: &str) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.retry_at(id, next_attempt_at, error).await?;
+  tx.commit().await
+}
+
+/// Counts a failed attempt at delivering a notification, and gives up on it.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn dead_letter(id: i64, error: &str) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.finish(id, Status::Dead, Some(error), Utc::now()).await?;
+  tx.commit().await
+}
+
+/// Counts the notifications that were given up on.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn count_dead() -> sqlx::Result<i64> {
+  let mut tx = db::begin().await?;
+  let res = tx.count_dead().await;
+  tx.commit().await?;
+  res
+}
+
+/// Removes the notifications that were sent before `before`, after which the same posts
+/// could be notified again. Notifications that were given up on are kept.
+/// Returns how many were removed.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn prune(before: DateTime<Utc>) -> sqlx::Result<u64> {
+  let mut tx = db::begin().await?;
+  let res = tx.prune(before).await;
+  tx.commit().await?;
+  res
+}
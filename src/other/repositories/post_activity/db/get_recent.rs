@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Returns when a watched user made their latest `limit` posts, from newest to oldest.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_recent(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+  limit: i64,
+) -> sqlx::Result<Vec<DateTime<Utc>>> {
+  let did = &**watched_did;
+  let posts = sqlx::query!(
+    r#"SELECT indexed_at AS "indexed_at: DateTime<Utc>" FROM "PostActivity"
+       WHERE watched_did = $1 ORDER BY indexed_at DESC LIMIT $2"#,
+    did,
+    limit
+  )
+  .fetch_all(&mut **tx)
+  .await?;
+
+  Ok(posts.into_iter().map(|post| post.indexed_at).collect())
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Records a post from a watched user, unless it was already recorded.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn insert(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+  post_uri: &str,
+  indexed_at: DateTime<Utc>,
+) -> sqlx::Result<()> {
+  let did = &**watched_did;
+  sqlx::query!(
+    r#"INSERT INTO "PostActivity" (watched_did, post_uri, indexed_at) VALUES ($1, $2, $3)
+       ON CONFLICT (watched_did, post_uri) DO NOTHING"#,
+    did,
+    post_uri,
+    indexed_at
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
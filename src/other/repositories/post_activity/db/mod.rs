@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{AppTransaction, Database, SqliteTransaction};
+
+mod insert;
+pub use insert::insert;
+
+mod prune;
+pub use prune::prune;
+
+mod get_recent;
+pub use get_recent::get_recent;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+/// The queries on post activity, run within a transaction.
+/// Implemented for the transactions of every storage backend.
+#[async_trait]
+pub trait PostActivityDb: Send {
+  /// Records a post from a watched user, unless it was already recorded.
+  async fn insert(
+    &mut self,
+    watched_did: &Did,
+    post_uri: &str,
+    indexed_at: DateTime<Utc>,
+  ) -> sqlx::Result<()>;
+  /// Removes all but the latest `keep` posts of a watched user.
+  async fn prune(&mut self, watched_did: &Did, keep: i64) -> sqlx::Result<()>;
+  /// Returns when a watched user made their latest `limit` posts, from newest to oldest.
+  async fn get_recent(&mut self, watched_did: &Did, limit: i64)
+    -> sqlx::Result<Vec<DateTime<Utc>>>;
+  /// Commits the transaction.
+  async fn commit(self: Box<Self>) -> sqlx::Result<()>;
+}
+
+/// Starts a transaction on the current storage backend.
+///
+/// # Errors
+///
+/// Fails when a transaction cannot be started.
+pub async fn begin() -> sqlx::Result<Box<dyn PostActivityDb>> {
+  Ok(match Database::get_tx().await? {
+    AppTransaction::Sqlite(tx) => Box::new(tx),
+    #[cfg(feature = "postgres")]
+    AppTransaction::Postgres(tx) => tx,
+  })
+}
+
+#[async_trait]
+impl PostActivityDb for SqliteTransaction {
+  async fn insert(
+    &mut self,
+    watched_did: &Did,
+    post_uri: &str,
+    indexed_at: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    insert(self, watched_did, post_uri, indexed_at).await
+  }
+
+  async fn prune(&mut self, watched_did: &Did, keep: i64) -> sqlx::Result<()> {
+    prune(self, watched_did, keep).await
+  }
+
+  async fn get_recent(
+    &mut self,
+    watched_did: &Did,
+    limit: i64,
+  ) -> sqlx::Result<Vec<DateTime<Utc>>> {
+    get_recent(self, watched_did, limit).await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
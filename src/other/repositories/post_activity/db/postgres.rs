@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use super::PostActivityDb;
+use crate::PgTransaction;
+
+#[async_trait]
+impl PostActivityDb for PgTransaction {
+  async fn insert(
+    &mut self,
+    watched_did: &Did,
+    post_uri: &str,
+    indexed_at: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"INSERT INTO "PostActivity" (watched_did, post_uri, indexed_at) VALUES ($1, $2, $3)
+         ON CONFLICT (watched_did, post_uri) DO NOTHING"#,
+    )
+    .bind(&**watched_did)
+    .bind(post_uri)
+    .bind(indexed_at)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn prune(&mut self, watched_did: &Did, keep: i64) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"DELETE FROM "PostActivity" WHERE watched_did = $1 AND post_uri NOT IN (
+           SELECT post_uri FROM "PostActivity" WHERE watched_did = $1
+           ORDER BY indexed_at DESC LIMIT $2
+         )"#,
+    )
+    .bind(&**watched_did)
+    .bind(keep)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn get_recent(
+    &mut self,
+    watched_did: &Did,
+    limit: i64,
+  ) -> sqlx::Result<Vec<DateTime<Utc>>> {
+    sqlx::query_scalar(
+      r#"SELECT indexed_at FROM "PostActivity"
+         WHERE watched_did = $1 ORDER BY indexed_at DESC LIMIT $2"#,
+    )
+    .bind(&**watched_did)
+    .bind(limit)
+    .fetch_all(&mut **self)
+    .await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
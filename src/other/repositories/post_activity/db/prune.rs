@@ -0,0 +1,24 @@
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Removes all but the latest `keep` posts of a watched user.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn prune(tx: &mut SqliteTransaction, watched_did: &Did, keep: i64) -> sqlx::Result<()> {
+  let did = &**watched_did;
+  sqlx::query!(
+    r#"DELETE FROM "PostActivity" WHERE watched_did = $1 AND post_uri NOT IN (
+         SELECT post_uri FROM "PostActivity" WHERE watched_did = $1
+         ORDER BY indexed_at DESC LIMIT $2
+       )"#,
+    did,
+    keep
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,49 @@
+//! This module contains all the re-exported interfaces for manipulating the
+//! database of when watched users have recently posted, used for picking how
+//! often to poll them.
+
+use chrono::{DateTime, Utc};
+use tracing::{event, Level};
+use utils::Did;
+
+mod db;
+
+/// How many of each user's latest posts are kept.
+pub static HISTORY_LENGTH: i64 = 20;
+
+/// Records new posts from a watched user, keeping only the latest `HISTORY_LENGTH` ones.
+pub async fn record(watched_did: &Did, posts: &[(String, DateTime<Utc>)]) {
+  let _ = async move {
+    let mut tx = db::begin().await?;
+    for (uri, indexed_at) in posts {
+      tx.insert(watched_did, uri, *indexed_at).await?;
+    }
+    tx.prune(watched_did, HISTORY_LENGTH).await?;
+    tx.commit().await
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to record post activity in the database: {e}"
+    );
+  });
+}
+
+/// Returns when a watched user made their latest posts, from newest to oldest.
+pub async fn get_recent(watched_did: &Did) -> Vec<DateTime<Utc>> {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get_recent(watched_did, HISTORY_LENGTH).await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get post activity from the database: {e}"
+    );
+  })
+  .unwrap_or_default()
+}
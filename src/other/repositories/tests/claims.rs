@@ -0,0 +1,145 @@
+//! Checks that instances sharing a Postgres database never pick up the same commands or
+//! notifications, by claiming them from several connections at once.
+//!
+//! Only runs against the database in `DATABASE_URL`, e.g.
+//! `DATABASE_URL=postgres://localhost/test cargo test -p repositories --features postgres`.
+#![cfg(feature = "postgres")]
+
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+
+use chrono::{TimeDelta, Utc};
+use repositories::{
+  command_inbox::{self, InboxCommand, Status},
+  notification_outbox::{self, Kind},
+  Backend, Database,
+};
+use tokio::task::JoinSet;
+use utils::Did;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_claims_never_overlap() {
+  let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
+  if !db_url.starts_with("postgres") {
+    eprintln!("DATABASE_URL is not a Postgres URL. Skipping.");
+    return;
+  }
+  assert_eq!(Database::backend().await, Backend::Postgres);
+  Database::migrate(&sqlx::migrate!("../../app/migrations/postgres"))
+    .await
+    .expect("Failed to migrate the test database");
+  let run = format!(
+    "{}-{}",
+    std::process::id(),
+    Utc::now().timestamp_nanos_opt().unwrap_or_default()
+  );
+
+  notifications_are_claimed_once(&run).await;
+  convos_are_claimed_whole(&run).await;
+
+  Database::disconnect().await;
+}
+
+async fn notifications_are_claimed_once(run: &str) {
+  let watcher: Did = Arc::from(format!("did:plc:watcher-{run}"));
+  for i in 0..20 {
+    let watched_did: Did = Arc::from(format!("did:plc:watched-{run}-{i}"));
+    notification_outbox::enqueue_notice(&watcher, &watched_did, Kind::Gone)
+      .await
+      .expect("Failed to enqueue a notification");
+  }
+
+  let lease = TimeDelta::minutes(1);
+  let mut claims = JoinSet::new();
+  for limit in [5, 5, 100, 100] {
+    claims.spawn(notification_outbox::claim_due(limit, lease));
+  }
+  let mut claimed = HashSet::new();
+  for claim in claims.join_all().await {
+    let ids = claim.expect("Failed to claim notifications");
+    for n in ids.into_iter().filter(|n| n.watcher_did == watcher) {
+      assert!(claimed.insert(n.id), "Notification {} claimed twice", n.id);
+    }
+  }
+  assert_eq!(claimed.len(), 20);
+
+  let again = notification_outbox::claim_due(100, lease)
+    .await
+    .expect("Failed to claim notifications");
+  assert!(again.iter().all(|n| n.watcher_did != watcher));
+
+  let ids: Vec<i64> = claimed.into_iter().collect();
+  notification_outbox::mark_sent(&ids).await;
+}
+
+async fn convos_are_claimed_whole(run: &str) {
+  let sender: Did = Arc::from(format!("did:plc:sender-{run}"));
+  let now = Utc::now();
+  let mut message_ids = Vec::new();
+  for convo in 0..5 {
+    for i in 0..3 {
+      let command = InboxCommand {
+        message_id: format!("{run}-{convo}-{i}"),
+        convo_id: format!("{run}-{convo}"),
+        sender_did: sender.clone(),
+        text: "!list".to_string(),
+        facets: None,
+        sent_at: now + TimeDelta::seconds(i),
+        attempts: 0,
+      };
+      command_inbox::receive(&command, i64::MAX)
+        .await
+        .expect("Failed to receive a command");
+      message_ids.push(command.message_id);
+    }
+  }
+
+  let lease = TimeDelta::minutes(1);
+  let mut claims = JoinSet::new();
+  for _ in 0..3 {
+    claims.spawn(command_inbox::claim_pending(lease));
+  }
+  // Which claim got each convo, and the commands it got in order
+  let mut by_convo: HashMap<String, (usize, Vec<String>)> = HashMap::new();
+  for (claim_idx, claim) in claims.join_all().await.into_iter().enumerate() {
+    let commands = claim.expect("Failed to claim commands");
+    for c in commands.into_iter().filter(|c| c.sender_did == sender) {
+      let (owner, ids) = by_convo
+        .entry(c.convo_id)
+        .or_insert_with(|| (claim_idx, Vec::new()));
+      assert_eq!(*owner, claim_idx, "Convo split between claims");
+      ids.push(c.message_id);
+    }
+  }
+  assert_eq!(by_convo.len(), 5);
+  for (convo_id, (_, ids)) in &by_convo {
+    let expected: Vec<String> = (0..3).map(|i| format!("{convo_id}-{i}")).collect();
+    assert_eq!(ids, &expected, "Commands out of order");
+  }
+
+  let again = command_inbox::claim_pending(lease)
+    .await
+    .expect("Failed to claim commands");
+  assert!(again.iter().all(|c| c.sender_did != sender));
+
+  // Released commands are picked up again right away
+  let released = &message_ids[..3];
+  command_inbox::release(released).await;
+  let again = command_inbox::claim_pending(lease)
+    .await
+    .expect("Failed to claim commands");
+  let reclaimed: Vec<&String> = again
+    .iter()
+    .filter(|c| c.sender_did == sender)
+    .map(|c| &c.message_id)
+    .collect();
+  assert_eq!(reclaimed, released.iter().collect::<Vec<_>>());
+
+  for message_id in &message_ids {
+    command_inbox::finish(message_id, Status::Done)
+      .await
+      .expect("Failed to finish a command");
+  }
+}
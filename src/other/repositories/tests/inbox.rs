@@ -0,0 +1,61 @@
+//! Checks that commands received at once, e.g. from several convos, are all saved, and that
+//! the limit of pending commands per sender holds, which needs every receive to get the write
+//! lock of the database right away.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use repositories::{
+  command_inbox::{self, InboxCommand, Received},
+  Database,
+};
+use tokio::task::JoinSet;
+use utils::Did;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_receives_are_all_saved_within_the_limit() {
+  let dir = std::env::temp_dir().join(format!("inbox-test-{}", std::process::id()));
+  std::fs::remove_dir_all(&dir).ok();
+  std::fs::create_dir_all(&dir).expect("Failed to create the test directory");
+  // Must be set before anything reads the environment
+  environment::set_overrides([(
+    "DATABASE_URL",
+    format!("sqlite://{}", dir.join("inbox-test.db").display()),
+  )])
+  .expect("Nothing else sets the overrides");
+  Database::migrate(&sqlx::migrate!("../../app/migrations/sqlite"))
+    .await
+    .expect("Failed to migrate the test database");
+
+  let sender: Did = Arc::from("did:plc:sender");
+  let now = Utc::now();
+  let mut tasks = JoinSet::new();
+  for i in 0..20 {
+    let command = InboxCommand {
+      message_id: format!("message-{i}"),
+      convo_id: format!("convo-{i}"),
+      sender_did: sender.clone(),
+      text: "!list".to_string(),
+      facets: None,
+      sent_at: now,
+      attempts: 0,
+    };
+    tasks.spawn(async move { command_inbox::receive(&command, 5).await });
+  }
+  let received: Vec<_> = tasks
+    .join_all()
+    .await
+    .into_iter()
+    .map(|res| res.expect("Failed to receive a command"))
+    .collect();
+
+  Database::disconnect().await;
+  std::fs::remove_dir_all(&dir).ok();
+
+  let queued = received.iter().filter(|r| **r == Received::Queued).count();
+  let rejected = received
+    .iter()
+    .filter(|r| **r == Received::Rejected)
+    .count();
+  assert_eq!((queued, rejected), (5, 15));
+}
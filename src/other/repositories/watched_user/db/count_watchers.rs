@@ -0,0 +1,21 @@
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Returns the number of watchers of a user.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn count_watchers(tx: &mut SqliteTransaction, watched_did: &Did) -> sqlx::Result<i64> {
+  let did = &**watched_did;
+  let count = sqlx::query!(
+    r#"SELECT COUNT(*) AS "count!: i64" FROM "Watch" WHERE watched_did = $1"#,
+    did
+  )
+  .fetch_one(&mut **tx)
+  .await?
+  .count;
+
+  Ok(count)
+}
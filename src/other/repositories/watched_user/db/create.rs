@@ -1,31 +1,21 @@
-use std::collections::HashSet;
-
+use chrono::Utc;
 use utils::Did;
 
-use crate::{watched_user::Watcher, AppTransaction, Loadable};
+use crate::{Loadable, SqliteTransaction};
 
-/// Creates a watched user.
+/// Creates a watched user, if not yet created. Posts are only considered new from this
+/// moment on.
 ///
 /// # Errors
 ///
-/// Returns an error if the query or serialization fails.
-pub async fn create(
-  tx: &mut AppTransaction,
-  watched_did: &Did,
-  watcher: Did,
-  with_replies: bool,
-) -> Loadable<()> {
-  let watchers = serde_json::to_string(&HashSet::from([Watcher {
-    did: watcher,
-    watch_replies: with_replies,
-  }]))
-  .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
-
+/// Returns an error if the query fails.
+pub async fn create(tx: &mut SqliteTransaction, watched_did: &Did) -> Loadable<()> {
   let did = &**watched_did;
+  let last_post_at = Utc::now();
   let rows = sqlx::query!(
-    r#"INSERT INTO "WatchedUser" (did, watchers) VALUES ($1, $2)"#,
+    r#"INSERT INTO "WatchedUser" (did, last_post_at) VALUES ($1, $2) ON CONFLICT (did) DO NOTHING"#,
     did,
-    watchers
+    last_post_at
   )
   .execute(&mut **tx)
   .await?
@@ -1,14 +1,17 @@
 use utils::Did;
 
-use crate::{AppTransaction, Loadable};
+use crate::{Loadable, SqliteTransaction};
 
-/// Deletes a watched user.
+/// Deletes a watched user, along with all of their watchers.
 ///
 /// # Errors
 ///
-/// Returns an error if the query fails.
-pub async fn delete(tx: &mut AppTransaction, watched_did: &Did) -> Loadable<()> {
+/// Returns an error if the queries fail.
+pub async fn delete(tx: &mut SqliteTransaction, watched_did: &Did) -> Loadable<()> {
   let did = &**watched_did;
+  sqlx::query!(r#"DELETE FROM "Watch" WHERE watched_did = $1"#, did)
+    .execute(&mut **tx)
+    .await?;
   let rows = sqlx::query!(r#"DELETE FROM "WatchedUser" WHERE did = $1"#, did)
     .execute(&mut **tx)
     .await?
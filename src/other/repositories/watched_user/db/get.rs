@@ -1,23 +1,33 @@
-use std::collections::HashSet;
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
 
 use utils::Did;
 
-use crate::{watched_user::watching::Watcher, AppTransaction};
+use crate::{watched_user::watching::Watcher, SqliteTransaction};
 
-/// Returns a set of all watchers of a user.
+/// Returns all watched users, along with their watchers.
 ///
 /// # Errors
 ///
 /// Returns an error if the query fails.
-pub async fn get(
-  tx: &mut AppTransaction,
-  watched_did: &Did,
-) -> Result<HashSet<Watcher>, sqlx::Error> {
-  let did = &**watched_did;
-  let watchers = sqlx::query!(r#"SELECT watchers FROM "WatchedUser" WHERE did = $1"#, did)
-    .fetch_one(&mut **tx)
-    .await?
-    .watchers;
+pub async fn get(tx: &mut SqliteTransaction) -> sqlx::Result<HashMap<Did, HashSet<Watcher>>> {
+  let watches = sqlx::query!(
+    r#"SELECT watched_did, watcher_did, watch_replies AS "watch_replies: bool" FROM "Watch""#
+  )
+  .fetch_all(&mut **tx)
+  .await?;
 
-  serde_json::from_str::<HashSet<Watcher>>(&watchers).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+  let mut watching: HashMap<Did, HashSet<Watcher>> = HashMap::new();
+  for watch in watches {
+    watching
+      .entry(Arc::from(watch.watched_did))
+      .or_default()
+      .insert(Watcher {
+        did: Arc::from(watch.watcher_did),
+        watch_replies: watch.watch_replies,
+      });
+  }
+  Ok(watching)
 }
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Returns the time of the last post from a watched user that the watchers
+/// were notified about.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_last_post_at(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+) -> Loadable<DateTime<Utc>> {
+  let did = &**watched_did;
+  let last_post_at = sqlx::query!(
+    r#"SELECT last_post_at AS "last_post_at: DateTime<Utc>" FROM "WatchedUser" WHERE did = $1"#,
+    did
+  )
+  .fetch_optional(&mut **tx)
+  .await?
+  .and_then(|user| user.last_post_at);
+
+  Ok(last_post_at)
+}
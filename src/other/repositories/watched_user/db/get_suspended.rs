@@ -0,0 +1,29 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Returns all suspended users, along with since when they've been suspended.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_suspended(
+  tx: &mut SqliteTransaction,
+) -> sqlx::Result<HashMap<Did, DateTime<Utc>>> {
+  let users = sqlx::query!(
+    r#"SELECT did, suspended_at AS "suspended_at!: DateTime<Utc>"
+    FROM "WatchedUser" WHERE suspended_at IS NOT NULL"#
+  )
+  .fetch_all(&mut **tx)
+  .await?;
+
+  Ok(
+    users
+      .into_iter()
+      .map(|user| (Arc::from(user.did), user.suspended_at))
+      .collect(),
+  )
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Returns since when a watched user has been suspended, if they are.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_suspended_at(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+) -> Loadable<DateTime<Utc>> {
+  let did = &**watched_did;
+  let suspended_at = sqlx::query!(
+    r#"SELECT suspended_at AS "suspended_at: DateTime<Utc>" FROM "WatchedUser" WHERE did = $1"#,
+    did
+  )
+  .fetch_optional(&mut **tx)
+  .await?
+  .and_then(|user| user.suspended_at);
+
+  Ok(suspended_at)
+}
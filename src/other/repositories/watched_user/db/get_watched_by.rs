@@ -0,0 +1,30 @@
+use std::{collections::HashSet, sync::Arc};
+
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Returns a set of all users watched by a user.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_watched_by(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+) -> sqlx::Result<HashSet<Did>> {
+  let did = &**watcher;
+  let rows = sqlx::query!(
+    r#"SELECT watched_did FROM "Watch" WHERE watcher_did = $1"#,
+    did
+  )
+  .fetch_all(&mut **tx)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|w| Arc::<str>::from(w.watched_did))
+      .collect(),
+  )
+}
@@ -1,34 +1,30 @@
+use chrono::Utc;
 use utils::Did;
 
-use crate::{watched_user::watching::Watcher, AppTransaction, Loadable};
+use crate::{Loadable, SqliteTransaction};
 
-use super::get;
-
-/// Inserts a watcher to a watched user.
+/// Inserts a watcher to a watched user, updating `watch_replies` if already present.
 ///
 /// # Errors
 ///
-/// Returns an error if the query or serialization fails.
+/// Returns an error if the query fails.
 pub async fn insert_watcher(
-  tx: &mut AppTransaction,
+  tx: &mut SqliteTransaction,
   watched_did: &Did,
   watcher: Did,
   with_replies: bool,
 ) -> Loadable<()> {
-  let mut watchers = get(tx, watched_did).await?;
-  let watcher = Watcher {
-    did: watcher,
-    watch_replies: with_replies,
-  };
-  watchers.insert(watcher);
-  let watchers_string =
-    serde_json::to_string(&watchers).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
-
-  let did = &**watched_did;
+  let user_did = &**watched_did;
+  let watcher = &*watcher;
+  let created_at = Utc::now();
   let rows = sqlx::query!(
-    r#"UPDATE "WatchedUser" SET watchers = $1 WHERE did = $2"#,
-    watchers_string,
-    did
+    r#"INSERT INTO "Watch" (watched_did, watcher_did, watch_replies, created_at)
+       VALUES ($1, $2, $3, $4)
+       ON CONFLICT (watched_did, watcher_did) DO UPDATE SET watch_replies = excluded.watch_replies"#,
+    user_did,
+    watcher,
+    with_replies,
+    created_at
   )
   .execute(&mut **tx)
   .await?
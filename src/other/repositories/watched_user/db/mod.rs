@@ -1,3 +1,14 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{
+  watched_user::{watching::Watcher, Profile},
+  AppTransaction, Database, Loadable, SqliteTransaction,
+};
+
 mod delete;
 pub use delete::delete;
 
@@ -12,3 +23,164 @@ pub use remove_watcher::remove_watcher;
 
 mod insert_watcher;
 pub use insert_watcher::insert_watcher;
+
+mod get_last_post_at;
+pub use get_last_post_at::get_last_post_at;
+
+mod set_last_post_at;
+pub use set_last_post_at::set_last_post_at;
+
+mod get_watched_by;
+pub use get_watched_by::get_watched_by;
+
+mod count_watchers;
+pub use count_watchers::count_watchers;
+
+mod suspend;
+pub use suspend::suspend;
+
+mod resume;
+pub use resume::resume;
+
+mod get_suspended_at;
+pub use get_suspended_at::get_suspended_at;
+
+mod get_suspended;
+pub use get_suspended::get_suspended;
+
+mod replace_profile;
+pub use replace_profile::replace_profile;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+/// The queries on watched users and their watchers, run within a transaction.
+/// Implemented for the transactions of every storage backend.
+#[async_trait]
+pub trait WatchedUserDb: Send {
+  /// Creates a watched user, if not yet created.
+  async fn create(&mut self, watched_did: &Did) -> Loadable<()>;
+  /// Inserts a watcher to a watched user, updating `watch_replies` if already present.
+  async fn insert_watcher(
+    &mut self,
+    watched_did: &Did,
+    watcher: Did,
+    with_replies: bool,
+  ) -> Loadable<()>;
+  /// Removes a watcher from a user.
+  async fn remove_watcher(&mut self, watched_did: &Did, watcher: Did) -> Loadable<()>;
+  /// Returns the number of watchers of a user.
+  async fn count_watchers(&mut self, watched_did: &Did) -> sqlx::Result<i64>;
+  /// Deletes a watched user, along with all of their watchers.
+  async fn delete(&mut self, watched_did: &Did) -> Loadable<()>;
+  /// Returns all watched users, along with their watchers.
+  async fn get(&mut self) -> sqlx::Result<HashMap<Did, HashSet<Watcher>>>;
+  /// Returns a set of all users watched by a user.
+  async fn get_watched_by(&mut self, watcher: &Did) -> sqlx::Result<HashSet<Did>>;
+  /// Returns the time of the last post from a watched user that the watchers
+  /// were notified about.
+  async fn get_last_post_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>>;
+  /// Moves the time of the last post from a watched user forward.
+  async fn set_last_post_at(
+    &mut self,
+    watched_did: &Did,
+    last_post_at: DateTime<Utc>,
+  ) -> Loadable<()>;
+  /// Marks a watched user as suspended since `at`, unless they're already suspended.
+  async fn suspend(&mut self, watched_did: &Did, at: DateTime<Utc>) -> Loadable<()>;
+  /// Marks a watched user as no longer suspended.
+  async fn resume(&mut self, watched_did: &Did) -> Loadable<()>;
+  /// Returns since when a watched user has been suspended, if they are.
+  async fn get_suspended_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>>;
+  /// Returns all suspended users, along with since when they've been suspended.
+  async fn get_suspended(&mut self) -> sqlx::Result<HashMap<Did, DateTime<Utc>>>;
+  /// Saves the profile of a watched user, returning the one it replaced.
+  async fn replace_profile(&mut self, watched_did: &Did, profile: &Profile) -> Loadable<Profile>;
+  /// Commits the transaction.
+  async fn commit(self: Box<Self>) -> sqlx::Result<()>;
+}
+
+/// Starts a transaction on the current storage backend.
+///
+/// # Errors
+///
+/// Fails when a transaction cannot be started.
+pub async fn begin() -> sqlx::Result<Box<dyn WatchedUserDb>> {
+  Ok(match Database::get_tx().await? {
+    AppTransaction::Sqlite(tx) => Box::new(tx),
+    #[cfg(feature = "postgres")]
+    AppTransaction::Postgres(tx) => tx,
+  })
+}
+
+#[async_trait]
+impl WatchedUserDb for SqliteTransaction {
+  async fn create(&mut self, watched_did: &Did) -> Loadable<()> {
+    create(self, watched_did).await
+  }
+
+  async fn insert_watcher(
+    &mut self,
+    watched_did: &Did,
+    watcher: Did,
+    with_replies: bool,
+  ) -> Loadable<()> {
+    insert_watcher(self, watched_did, watcher, with_replies).await
+  }
+
+  async fn remove_watcher(&mut self, watched_did: &Did, watcher: Did) -> Loadable<()> {
+    remove_watcher(self, watched_did, watcher).await
+  }
+
+  async fn count_watchers(&mut self, watched_did: &Did) -> sqlx::Result<i64> {
+    count_watchers(self, watched_did).await
+  }
+
+  async fn delete(&mut self, watched_did: &Did) -> Loadable<()> {
+    delete(self, watched_did).await
+  }
+
+  async fn get(&mut self) -> sqlx::Result<HashMap<Did, HashSet<Watcher>>> {
+    get(self).await
+  }
+
+  async fn get_watched_by(&mut self, watcher: &Did) -> sqlx::Result<HashSet<Did>> {
+    get_watched_by(self, watcher).await
+  }
+
+  async fn get_last_post_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>> {
+    get_last_post_at(self, watched_did).await
+  }
+
+  async fn set_last_post_at(
+    &mut self,
+    watched_did: &Did,
+    last_post_at: DateTime<Utc>,
+  ) -> Loadable<()> {
+    set_last_post_at(self, watched_did, last_post_at).await
+  }
+
+  async fn suspend(&mut self, watched_did: &Did, at: DateTime<Utc>) -> Loadable<()> {
+    suspend(self, watched_did, at).await
+  }
+
+  async fn resume(&mut self, watched_did: &Did) -> Loadable<()> {
+    resume(self, watched_did).await
+  }
+
+  async fn get_suspended_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>> {
+    get_suspended_at(self, watched_did).await
+  }
+
+  async fn get_suspended(&mut self) -> sqlx::Result<HashMap<Did, DateTime<Utc>>> {
+    get_suspended(self).await
+  }
+
+  async fn replace_profile(&mut self, watched_did: &Did, profile: &Profile) -> Loadable<Profile> {
+    replace_profile(self, watched_did, profile).await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
@@ -0,0 +1,223 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use super::WatchedUserDb;
+use crate::{
+  watched_user::{watching::Watcher, Profile},
+  Loadable, PgTransaction,
+};
+
+#[async_trait]
+impl WatchedUserDb for PgTransaction {
+  async fn create(&mut self, watched_did: &Did) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"INSERT INTO "WatchedUser" (did, last_post_at) VALUES ($1, $2) ON CONFLICT (did) DO NOTHING"#,
+    )
+    .bind(&**watched_did)
+    .bind(Utc::now())
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn insert_watcher(
+    &mut self,
+    watched_did: &Did,
+    watcher: Did,
+    with_replies: bool,
+  ) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"INSERT INTO "Watch" (watched_did, watcher_did, watch_replies, created_at)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (watched_did, watcher_did) DO UPDATE SET watch_replies = excluded.watch_replies"#,
+    )
+    .bind(&**watched_did)
+    .bind(&*watcher)
+    .bind(with_replies)
+    .bind(Utc::now())
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn remove_watcher(&mut self, watched_did: &Did, watcher: Did) -> Loadable<()> {
+    let rows = sqlx::query(r#"DELETE FROM "Watch" WHERE watched_did = $1 AND watcher_did = $2"#)
+      .bind(&**watched_did)
+      .bind(&*watcher)
+      .execute(&mut **self)
+      .await?
+      .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn count_watchers(&mut self, watched_did: &Did) -> sqlx::Result<i64> {
+    sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Watch" WHERE watched_did = $1"#)
+      .bind(&**watched_did)
+      .fetch_one(&mut **self)
+      .await
+  }
+
+  async fn delete(&mut self, watched_did: &Did) -> Loadable<()> {
+    sqlx::query(r#"DELETE FROM "Watch" WHERE watched_did = $1"#)
+      .bind(&**watched_did)
+      .execute(&mut **self)
+      .await?;
+    let rows = sqlx::query(r#"DELETE FROM "WatchedUser" WHERE did = $1"#)
+      .bind(&**watched_did)
+      .execute(&mut **self)
+      .await?
+      .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn get(&mut self) -> sqlx::Result<HashMap<Did, HashSet<Watcher>>> {
+    let watches: Vec<(String, String, bool)> =
+      sqlx::query_as(r#"SELECT watched_did, watcher_did, watch_replies FROM "Watch""#)
+        .fetch_all(&mut **self)
+        .await?;
+
+    let mut watching: HashMap<Did, HashSet<Watcher>> = HashMap::new();
+    for (watched_did, watcher_did, watch_replies) in watches {
+      watching
+        .entry(Arc::from(watched_did))
+        .or_default()
+        .insert(Watcher {
+          did: Arc::from(watcher_did),
+          watch_replies,
+        });
+    }
+    Ok(watching)
+  }
+
+  async fn get_watched_by(&mut self, watcher: &Did) -> sqlx::Result<HashSet<Did>> {
+    let rows: Vec<String> =
+      sqlx::query_scalar(r#"SELECT watched_did FROM "Watch" WHERE watcher_did = $1"#)
+        .bind(&**watcher)
+        .fetch_all(&mut **self)
+        .await?;
+
+    Ok(rows.into_iter().map(Arc::<str>::from).collect())
+  }
+
+  async fn get_last_post_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>> {
+    let last_post_at: Option<Option<DateTime<Utc>>> =
+      sqlx::query_scalar(r#"SELECT last_post_at FROM "WatchedUser" WHERE did = $1"#)
+        .bind(&**watched_did)
+        .fetch_optional(&mut **self)
+        .await?;
+
+    Ok(last_post_at.flatten())
+  }
+
+  async fn set_last_post_at(
+    &mut self,
+    watched_did: &Did,
+    last_post_at: DateTime<Utc>,
+  ) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"UPDATE "WatchedUser" SET last_post_at = $1
+         WHERE did = $2 AND (last_post_at IS NULL OR last_post_at < $1)"#,
+    )
+    .bind(last_post_at)
+    .bind(&**watched_did)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn suspend(&mut self, watched_did: &Did, at: DateTime<Utc>) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"UPDATE "WatchedUser" SET suspended_at = $1 WHERE did = $2 AND suspended_at IS NULL"#,
+    )
+    .bind(at)
+    .bind(&**watched_did)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn resume(&mut self, watched_did: &Did) -> Loadable<()> {
+    let rows = sqlx::query(
+      r#"UPDATE "WatchedUser" SET suspended_at = NULL WHERE did = $1 AND suspended_at IS NOT NULL"#,
+    )
+    .bind(&**watched_did)
+    .execute(&mut **self)
+    .await?
+    .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn get_suspended_at(&mut self, watched_did: &Did) -> Loadable<DateTime<Utc>> {
+    let suspended_at: Option<Option<DateTime<Utc>>> =
+      sqlx::query_scalar(r#"SELECT suspended_at FROM "WatchedUser" WHERE did = $1"#)
+        .bind(&**watched_did)
+        .fetch_optional(&mut **self)
+        .await?;
+
+    Ok(suspended_at.flatten())
+  }
+
+  async fn get_suspended(&mut self) -> sqlx::Result<HashMap<Did, DateTime<Utc>>> {
+    let users: Vec<(String, DateTime<Utc>)> = sqlx::query_as(
+      r#"SELECT did, suspended_at FROM "WatchedUser" WHERE suspended_at IS NOT NULL"#,
+    )
+    .fetch_all(&mut **self)
+    .await?;
+
+    Ok(
+      users
+        .into_iter()
+        .map(|(did, suspended_at)| (Arc::from(did), suspended_at))
+        .collect(),
+    )
+  }
+
+  async fn replace_profile(&mut self, watched_did: &Did, profile: &Profile) -> Loadable<Profile> {
+    let previous: Option<(Option<String>, Option<String>, Option<String>)> = sqlx::query_as(
+      r#"SELECT handle, display_name, avatar FROM "WatchedUser" WHERE did = $1 FOR UPDATE"#,
+    )
+    .bind(&**watched_did)
+    .fetch_optional(&mut **self)
+    .await?;
+    let Some((handle, display_name, avatar)) = previous else {
+      return Ok(None);
+    };
+
+    sqlx::query(
+      r#"UPDATE "WatchedUser" SET handle = $1, display_name = $2, avatar = $3 WHERE did = $4"#,
+    )
+    .bind(&profile.handle)
+    .bind(&profile.display_name)
+    .bind(&profile.avatar)
+    .bind(&**watched_did)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(Some(Profile {
+      handle,
+      display_name,
+      avatar,
+    }))
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
@@ -1,40 +1,27 @@
 use utils::Did;
 
-use crate::{watched_user::watching::Watcher, AppTransaction, Loadable};
-
-use super::get;
+use crate::{Loadable, SqliteTransaction};
 
 /// Removes a watcher from a user.
 ///
 /// # Errors
 ///
-/// Returns an error if the queries or serialization fails.
+/// Returns an error if the query fails.
 pub async fn remove_watcher(
-  tx: &mut AppTransaction,
+  tx: &mut SqliteTransaction,
   watched_did: &Did,
   watcher: Did,
 ) -> Loadable<()> {
-  let mut watchers = get(tx, watched_did).await?;
-
-  if watchers.remove(&Watcher {
-    did: watcher,
-    watch_replies: false, // watch_replies not used in the hash
-  }) {
-    let watchers_string =
-      serde_json::to_string(&watchers).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
-
-    let did = &**watched_did;
-    let rows = sqlx::query!(
-      r#"UPDATE "WatchedUser" SET watchers = $1 WHERE did = $2"#,
-      watchers_string,
-      did
-    )
-    .execute(&mut **tx)
-    .await?
-    .rows_affected();
-
-    return Ok(if rows > 0 { Some(()) } else { None });
-  }
+  let user_did = &**watched_did;
+  let watcher = &*watcher;
+  let rows = sqlx::query!(
+    r#"DELETE FROM "Watch" WHERE watched_did = $1 AND watcher_did = $2"#,
+    user_did,
+    watcher
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
 
-  Ok(Some(()))
+  Ok(if rows > 0 { Some(()) } else { None })
 }
@@ -0,0 +1,50 @@
+use utils::Did;
+
+use crate::{watched_user::Profile, Loadable, SqliteTransaction};
+
+/// Saves the profile of a watched user, returning the one it replaced.
+///
+/// # Returns
+/// `None` if the user is not being watched.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn replace_profile(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+  profile: &Profile,
+) -> Loadable<Profile> {
+  let did = &**watched_did;
+  // Written to before being read, so that the transaction holds the write lock from the start.
+  // Sqlite can't upgrade a transaction that has only read so far while others are writing.
+  let rows = sqlx::query!(r#"UPDATE "WatchedUser" SET did = did WHERE did = $1"#, did)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+  if rows == 0 {
+    return Ok(None);
+  }
+
+  let previous = sqlx::query!(
+    r#"SELECT handle, display_name, avatar FROM "WatchedUser" WHERE did = $1"#,
+    did
+  )
+  .fetch_one(&mut **tx)
+  .await?;
+  sqlx::query!(
+    r#"UPDATE "WatchedUser" SET handle = $1, display_name = $2, avatar = $3 WHERE did = $4"#,
+    profile.handle,
+    profile.display_name,
+    profile.avatar,
+    did
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(Some(Profile {
+    handle: previous.handle,
+    display_name: previous.display_name,
+    avatar: previous.avatar,
+  }))
+}
@@ -0,0 +1,21 @@
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Marks a watched user as no longer suspended.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn resume(tx: &mut SqliteTransaction, watched_did: &Did) -> Loadable<()> {
+  let did = &**watched_did;
+  let rows = sqlx::query!(
+    r#"UPDATE "WatchedUser" SET suspended_at = NULL WHERE did = $1 AND suspended_at IS NOT NULL"#,
+    did
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
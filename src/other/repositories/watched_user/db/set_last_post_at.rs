@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Moves the time of the last post from a watched user forward.
+/// Does nothing if the stored time is already more recent.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_last_post_at(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+  last_post_at: DateTime<Utc>,
+) -> Loadable<()> {
+  let did = &**watched_did;
+  let rows = sqlx::query!(
+    r#"UPDATE "WatchedUser" SET last_post_at = $1
+    WHERE did = $2 AND (last_post_at IS NULL OR julianday(last_post_at) < julianday($1))"#,
+    last_post_at,
+    did
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
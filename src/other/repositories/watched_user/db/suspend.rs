@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Marks a watched user as suspended since `at`.
+/// Does nothing if they're already suspended, so that the time they were first suspended is kept.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn suspend(
+  tx: &mut SqliteTransaction,
+  watched_did: &Did,
+  at: DateTime<Utc>,
+) -> Loadable<()> {
+  let did = &**watched_did;
+  let rows = sqlx::query!(
+    r#"UPDATE "WatchedUser" SET suspended_at = $1 WHERE did = $2 AND suspended_at IS NULL"#,
+    at,
+    did
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
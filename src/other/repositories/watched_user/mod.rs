@@ -1,9 +1,11 @@
 //! This module contains all the re-exported interfaces for manipulating the
 //! memory repository and database of watched users.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::Database;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use tokio::sync::Mutex;
 use tracing::{event, Level};
 
 mod watching;
@@ -13,49 +15,130 @@ use watching::Watching;
 
 mod db;
 
-/// Watches a user.
-/// Returns true if the user is only now being watched (first watcher).
-pub async fn watch(watched_did: Did, watcher: Did, with_replies: bool) -> bool {
-  if Watching::watch(watched_did.clone(), watcher.clone(), with_replies).await {
-    tokio::spawn(handled_db_create(watched_did, watcher, with_replies));
-    true
-  } else {
-    tokio::spawn(handled_db_insert_watcher(
-      watched_did,
-      watcher,
-      with_replies,
-    ));
-    false
+lazy_static! {
+  /// Serializes changes to the watchlist, so that the memory repository is always
+  /// updated in the same order as the database.
+  static ref WRITE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// What's known about how a watched user presents themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+  pub handle: Option<String>,
+  pub display_name: Option<String>,
+  pub avatar: Option<String>,
+}
+
+/// Makes a watcher watch several users.
+///
+/// If the watcher was already watching one of them, `with_replies` is updated. All changes
+/// are committed to the database in a single transaction, and only then applied to the
+/// memory repository.
+/// Returns the users that are only now being watched (first watcher).
+///
+/// # Errors
+///
+/// Returns an error if any of the queries fail, in which case nothing is changed.
+pub async fn watch(
+  watcher: &Did,
+  watched_dids: &[Did],
+  with_replies: bool,
+) -> sqlx::Result<Vec<Did>> {
+  let lock = WRITE_LOCK.lock().await;
+
+  let mut tx = db::begin().await?;
+  let mut newly_watched = Vec::new();
+  for watched_did in watched_dids {
+    if tx.create(watched_did).await?.is_some() {
+      newly_watched.push(watched_did.clone());
+    }
+    tx.insert_watcher(watched_did, watcher.clone(), with_replies)
+      .await?;
+  }
+  tx.commit().await?;
+
+  for watched_did in watched_dids {
+    Watching::watch(watched_did.clone(), watcher.clone(), with_replies).await;
   }
+  drop(lock);
+
+  Ok(newly_watched)
 }
 
-/// Unwatches a user.
-/// Returns `Some(true)` if the user is no longer being watched (last watcher).
-/// Returns `Some(false)` if the user is still being watched by other users.
-/// Returns `None` if the user is not even being watched to begin with.
-pub async fn unwatch(watched_did: Did, watcher: Did) -> Option<bool> {
-  match Watching::unwatch(&watched_did, watcher.clone()).await {
-    Some(true) => {
-      tokio::spawn(handled_db_delete(watched_did));
-      Some(true)
+/// Makes a watcher unwatch several users.
+///
+/// Users that are left without any watchers are removed altogether. All changes are
+/// committed to the database in a single transaction, and only then applied to the
+/// memory repository.
+/// Returns the users that are no longer being watched (last watcher).
+///
+/// # Errors
+///
+/// Returns an error if any of the queries fail, in which case nothing is changed.
+pub async fn unwatch(watcher: &Did, watched_dids: &[Did]) -> sqlx::Result<Vec<Did>> {
+  let lock = WRITE_LOCK.lock().await;
+
+  let mut tx = db::begin().await?;
+  let mut no_longer_watched = Vec::new();
+  for watched_did in watched_dids {
+    if tx
+      .remove_watcher(watched_did, watcher.clone())
+      .await?
+      .is_none()
+    {
+      continue;
     }
-    Some(false) => {
-      tokio::spawn(handled_db_remove_watcher(watched_did, watcher));
-      Some(false)
+    if tx.count_watchers(watched_did).await? == 0 {
+      tx.delete(watched_did).await?;
+      no_longer_watched.push(watched_did.clone());
     }
-    None => None,
   }
+  tx.commit().await?;
+
+  for watched_did in watched_dids {
+    Watching::unwatch(watched_did, watcher.clone()).await;
+  }
+  drop(lock);
+
+  Ok(no_longer_watched)
 }
 
 /// Unwatches a user from all watchers.
+///
+/// The user is deleted from the database along with their watchers, and only then removed
+/// from the memory repository.
 /// Returns a `Some` of a set of all watchers that were watching the user.
 /// Returns `None` if the user is not even being watched to begin with.
-pub async fn unwatch_all(watched_did: &Did, sync_with_db: bool) -> Option<HashSet<Watcher>> {
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is changed.
+pub async fn unwatch_all(watched_did: &Did) -> sqlx::Result<Option<HashSet<Watcher>>> {
+  let lock = WRITE_LOCK.lock().await;
+
+  let mut tx = db::begin().await?;
+  tx.delete(watched_did).await?;
+  tx.commit().await?;
+
   let watchers = Watching::unwatch_all(watched_did).await;
-  if sync_with_db {
-    tokio::spawn(handled_db_delete(watched_did.clone()));
-  }
-  watchers
+  drop(lock);
+
+  Ok(watchers)
+}
+
+/// Reloads the memory repository from the database, so that changes made by other instances
+/// sharing it are picked up.
+///
+/// Returns the users that weren't being watched before.
+///
+/// # Errors
+///
+/// Returns an error if the query fails, in which case nothing is changed.
+pub async fn reload() -> sqlx::Result<Vec<Did>> {
+  let lock = WRITE_LOCK.lock().await;
+  let res = Watching::reload().await;
+  drop(lock);
+  res
 }
 
 /// Returns a `Some` of set of all watchers of a user.
@@ -70,8 +153,20 @@ pub async fn get_watching() -> HashSet<Did> {
 }
 
 /// Returns a set of all users watched by a user.
-pub async fn get_watched_by(watcher: &Did) -> HashSet<Did> {
-  Watching::get_watched_by(watcher).await
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get_watched_by(watcher: &Did) -> sqlx::Result<HashSet<Did>> {
+  let mut tx = db::begin().await?;
+  let res = tx.get_watched_by(watcher).await;
+  tx.commit().await?;
+  res
+}
+
+/// Returns how many users are watched, and how many distinct users are watching them.
+pub async fn count() -> (usize, usize) {
+  Watching::count().await
 }
 
 /// Returns true if a user is being watched.
@@ -79,26 +174,32 @@ pub async fn is_watched(watched_did: &Did) -> bool {
   Watching::is_watched(watched_did).await
 }
 
-/// Auxiliary function to handle database create operations.
-/// Used when a user is being watched by a new watcher, and was not being watched
-/// beforehand.
-async fn handled_db_create(watched_did: Did, watcher: Did, with_replies: bool) {
-  let _ = async move {
-    let mut tx = Database::get_tx().await?;
-    let res = db::create(&mut tx, &watched_did, watcher, with_replies).await;
+/// Returns the time of the last post from a watched user that their watchers were notified about.
+/// Returns `None` if the user is not being watched, or if the query fails.
+pub async fn get_last_post_at(watched_did: &Did) -> Option<DateTime<Utc>> {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get_last_post_at(watched_did).await;
     tx.commit().await?;
     res
   }
   .await
-  .map_err(|e| event!(Level::WARN, "Failed to save watched user to Sqlite: {e}"));
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get last post time from the database: {e}"
+    );
+  })
+  .ok()
+  .flatten()
 }
 
-/// Auxiliary function to handle database delete operations.
-/// Used when a user is no longer being watched by any watcher.
-async fn handled_db_delete(watched_did: Did) {
+/// Saves the time of the last post from a watched user that their watchers were notified about,
+/// so that posts made while the bot is down can be recovered after a restart.
+pub async fn set_last_post_at(watched_did: &Did, last_post_at: DateTime<Utc>) {
   let _ = async move {
-    let mut tx = Database::get_tx().await?;
-    let res = db::delete(&mut tx, &watched_did).await;
+    let mut tx = db::begin().await?;
+    let res = tx.set_last_post_at(watched_did, last_post_at).await;
     tx.commit().await?;
     res
   }
@@ -106,35 +207,110 @@ async fn handled_db_delete(watched_did: Did) {
   .map_err(|e| {
     event!(
       Level::WARN,
-      "Failed to delete watched user from Sqlite: {e}"
+      "Failed to save last post time to the database: {e}"
     );
   });
 }
 
-/// Auxiliary function to handle database insert operations for a watcher.
-/// Used when a user is being watched by a new watcher, but was already being watched
-/// by other watchers.
-async fn handled_db_insert_watcher(watched_did: Did, watcher: Did, with_replies: bool) {
-  let _ = async move {
-    let mut tx = Database::get_tx().await?;
-    let res = db::insert_watcher(&mut tx, &watched_did, watcher, with_replies).await;
+/// Suspends a watched user, e.g. because they couldn't be reached for a while.
+///
+/// They're kept around along with their watchers until they can be reached again.
+/// Returns since when they've been suspended, which is only now if they weren't already.
+/// Returns `None` if the user is not being watched, or if the query fails.
+pub async fn suspend(watched_did: &Did) -> Option<DateTime<Utc>> {
+  async move {
+    let mut tx = db::begin().await?;
+    tx.suspend(watched_did, Utc::now()).await?;
+    let res = tx.get_suspended_at(watched_did).await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to save suspension of watched user to the database: {e}"
+    );
+  })
+  .ok()
+  .flatten()
+}
+
+/// Returns whether a watched user is suspended.
+/// Returns `false` if the query fails.
+pub async fn is_suspended(watched_did: &Did) -> bool {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get_suspended_at(watched_did).await;
     tx.commit().await?;
     res
   }
   .await
-  .map_err(|e| event!(Level::WARN, "Failed to insert new watcher in Sqlite: {e}"));
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get suspension of watched user from the database: {e}"
+    );
+  })
+  .is_ok_and(|suspended_at| suspended_at.is_some())
 }
 
-/// Auxiliary function to handle database remove operations for a watcher.
-/// Used when a user is no longer being watched by a watcher, but there are still
-/// other watchers.
-async fn handled_db_remove_watcher(watched_did: Did, watcher: Did) {
+/// Lifts the suspension of a watched user, once they can be reached again.
+pub async fn resume(watched_did: &Did) {
   let _ = async move {
-    let mut tx = Database::get_tx().await?;
-    let res = db::remove_watcher(&mut tx, &watched_did, watcher).await;
+    let mut tx = db::begin().await?;
+    let res = tx.resume(watched_did).await;
     tx.commit().await?;
     res
   }
   .await
-  .map_err(|e| event!(Level::WARN, "Failed to remove watcher from Sqlite: {e}"));
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to lift suspension of watched user in the database: {e}"
+    );
+  });
+}
+
+/// Returns all suspended users, along with since when they've been suspended.
+/// Returns an empty map if the query fails.
+pub async fn get_suspended() -> HashMap<Did, DateTime<Utc>> {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get_suspended().await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .unwrap_or_else(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get suspended users from the database: {e}"
+    );
+    HashMap::new()
+  })
+}
+
+/// Saves the latest profile of a watched user.
+///
+/// Returns the profile it replaced if it differs, which is empty if none was saved before.
+/// Returns `None` if the profile didn't change, if the user is not being watched, or if the
+/// query fails.
+pub async fn set_profile(watched_did: &Did, profile: &Profile) -> Option<Profile> {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.replace_profile(watched_did, profile).await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to save profile of watched user to the database: {e}"
+    );
+  })
+  .ok()
+  .flatten()
+  .filter(|previous| previous != profile)
 }
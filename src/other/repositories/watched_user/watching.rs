@@ -1,15 +1,13 @@
 use async_once::AsyncOnce;
 use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
 use std::{
   collections::{HashMap, HashSet},
   hash::Hasher,
-  sync::Arc,
 };
 use tokio::sync::RwLock;
 use utils::Did;
 
-use crate::Database;
+use super::db;
 
 lazy_static! {
   /// Current state of the memory repository.
@@ -26,6 +24,25 @@ impl Watching {
     })))
   }
 
+  /// Replaces the whole state with the one in the database.
+  /// Returns the users that weren't being watched before.
+  ///
+  /// # Errors
+  /// When the query fails, in which case nothing is changed.
+  pub async fn reload() -> sqlx::Result<Vec<Did>> {
+    let watching = get_watching().await?;
+    let mut state_rw = STATE.get().await.0.write().await;
+    let newly_watched = watching
+      .keys()
+      .filter(|watched_did| !state_rw.contains_key(*watched_did))
+      .cloned()
+      .collect();
+    *state_rw = watching;
+    drop(state_rw);
+
+    Ok(newly_watched)
+  }
+
   /// Returns true if watched user is only now being watched
   pub async fn watch(watched_did: Did, watcher: Did, with_replies: bool) -> bool {
     let mut is_new = false;
@@ -79,20 +96,19 @@ impl Watching {
     STATE.get().await.0.read().await.keys().cloned().collect()
   }
 
-  pub async fn get_watched_by(watcher_did: &Did) -> HashSet<Did> {
-    let mut watched_by = HashSet::new();
-    #[expect(clippy::significant_drop_in_scrutinee)] // Clippy bug, lol
-    for (did, watchers) in STATE.get().await.0.read().await.iter() {
-      if watchers.contains(watcher_did).await {
-        watched_by.insert(did.clone());
-      }
-    }
-    watched_by
-  }
-
   pub async fn is_watched(watched_did: &Did) -> bool {
     STATE.get().await.0.read().await.contains_key(watched_did)
   }
+
+  /// Returns how many users are watched, and how many distinct users are watching them.
+  pub async fn count() -> (usize, usize) {
+    let state = STATE.get().await.0.read().await;
+    let mut watchers = HashSet::new();
+    for watched in state.values() {
+      watchers.extend(watched.0.read().await.iter().map(|w| w.did.clone()));
+    }
+    (state.len(), watchers.len())
+  }
 }
 
 /// A set of watchers for a watched user.
@@ -103,8 +119,9 @@ impl Watchers {
     Self(RwLock::from(HashSet::new()))
   }
 
+  /// Replaces the watcher if already present, so that `with_replies` is updated
   async fn add(&self, watcher: Did, with_replies: bool) {
-    self.0.write().await.insert(Watcher {
+    self.0.write().await.replace(Watcher {
       did: watcher,
       watch_replies: with_replies,
     });
@@ -124,10 +141,6 @@ impl Watchers {
   async fn clone(&self) -> HashSet<Watcher> {
     self.0.read().await.clone()
   }
-
-  async fn contains(&self, watcher: &Did) -> bool {
-    self.0.read().await.iter().any(|w| w.did == *watcher)
-  }
 }
 impl From<HashSet<Watcher>> for Watchers {
   fn from(watchers: HashSet<Watcher>) -> Self {
@@ -136,11 +149,9 @@ impl From<HashSet<Watcher>> for Watchers {
 }
 
 /// A user watching another user.
-#[derive(Debug, Clone, Serialize, Deserialize, Eq)]
+#[derive(Debug, Clone, Eq)]
 pub struct Watcher {
-  #[serde(rename = "0")]
   pub did: Did,
-  #[serde(rename = "1")]
   pub watch_replies: bool,
 }
 impl std::hash::Hash for Watcher {
@@ -154,24 +165,20 @@ impl PartialEq for Watcher {
   }
 }
 
-/// A method only used once at the initialization of the program to get the initial state of watched users
-/// for the memory repository. Retrieves all watched users from the database.
+/// A method used at the initialization of the program, and whenever the memory repository is
+/// reloaded, to get the state of watched users. Retrieves all watched users from the database.
 ///
 /// # Errors
 /// When the query fails.
 async fn get_watching() -> sqlx::Result<HashMap<Did, Watchers>> {
-  let mut tx = Database::get_tx().await?;
-  let watched_users = sqlx::query!(r#"SELECT * FROM "WatchedUser""#)
-    .fetch_all(&mut *tx)
-    .await?;
+  let mut tx = db::begin().await?;
+  let watching = tx.get().await?;
   tx.commit().await?;
 
-  let mut watching = HashMap::new();
-  for user in watched_users {
-    let did = Arc::<str>::from(user.did);
-    let watchers = serde_json::from_str::<HashSet<Watcher>>(&user.watchers)
-      .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
-    watching.insert(did, Watchers::from(watchers));
-  }
-  Ok(watching)
+  Ok(
+    watching
+      .into_iter()
+      .map(|(did, watchers)| (did, Watchers::from(watchers)))
+      .collect(),
+  )
 }
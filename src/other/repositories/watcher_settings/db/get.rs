@@ -0,0 +1,46 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use tracing::{event, Level};
+use utils::Did;
+
+use crate::{
+  watcher_settings::{Digest, QuietHours, WatcherSettings},
+  Loadable, SqliteTransaction,
+};
+
+/// Returns the settings of a watcher.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn get(tx: &mut SqliteTransaction, watcher: &Did) -> Loadable<WatcherSettings> {
+  let did = &**watcher;
+  let settings = sqlx::query!(
+    r#"SELECT
+         group_posts AS "group_posts: bool",
+         digest AS "digest: Digest",
+         digest_sent_at AS "digest_sent_at: DateTime<Utc>",
+         timezone,
+         quiet_start AS "quiet_start: NaiveTime",
+         quiet_end AS "quiet_end: NaiveTime"
+       FROM "WatcherSettings" WHERE did = $1"#,
+    did
+  )
+  .fetch_optional(&mut **tx)
+  .await?;
+
+  Ok(settings.map(|s| {
+    WatcherSettings {
+      group_posts: s.group_posts,
+      digest: s.digest,
+      digest_sent_at: s.digest_sent_at,
+      timezone: s.timezone.parse().unwrap_or_else(|e| {
+        event!(Level::WARN, "Invalid timezone saved for {did}: {e}");
+        chrono_tz::UTC
+      }),
+      quiet_hours: s
+        .quiet_start
+        .zip(s.quiet_end)
+        .map(|(start, end)| QuietHours { start, end }),
+    }
+  }))
+}
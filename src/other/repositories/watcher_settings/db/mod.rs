@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use utils::Did;
+
+use crate::{
+  watcher_settings::{Digest, QuietHours, WatcherSettings},
+  AppTransaction, Database, Loadable, SqliteTransaction,
+};
+
+mod get;
+pub use get::get;
+
+mod set_group_posts;
+pub use set_group_posts::set_group_posts;
+
+mod set_digest;
+pub use set_digest::set_digest;
+
+mod set_digest_sent_at;
+pub use set_digest_sent_at::set_digest_sent_at;
+
+mod set_timezone;
+pub use set_timezone::set_timezone;
+
+mod set_quiet_hours;
+pub use set_quiet_hours::set_quiet_hours;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+
+/// The queries on watchers' settings, run within a transaction.
+/// Implemented for the transactions of every storage backend.
+#[async_trait]
+pub trait WatcherSettingsDb: Send {
+  /// Returns the settings of a watcher.
+  async fn get(&mut self, watcher: &Did) -> Loadable<WatcherSettings>;
+  /// Saves whether a watcher wants several new posts in a single message.
+  async fn set_group_posts(&mut self, watcher: &Did, group_posts: bool) -> sqlx::Result<()>;
+  /// Saves how often a watcher wants to receive their notifications, counting the next
+  /// digest from `now`.
+  async fn set_digest(
+    &mut self,
+    watcher: &Did,
+    digest: Digest,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()>;
+  /// Saves when the last digest was sent to a watcher.
+  async fn set_digest_sent_at(
+    &mut self,
+    watcher: &Did,
+    digest_sent_at: DateTime<Utc>,
+  ) -> Loadable<()>;
+  /// Saves the timezone of a watcher.
+  async fn set_timezone(&mut self, watcher: &Did, timezone: Tz) -> sqlx::Result<()>;
+  /// Saves the quiet hours of a watcher.
+  async fn set_quiet_hours(
+    &mut self,
+    watcher: &Did,
+    quiet_hours: Option<QuietHours>,
+  ) -> sqlx::Result<()>;
+  /// Commits the transaction.
+  async fn commit(self: Box<Self>) -> sqlx::Result<()>;
+}
+
+/// Starts a transaction on the current storage backend.
+///
+/// # Errors
+///
+/// Fails when a transaction cannot be started.
+pub async fn begin() -> sqlx::Result<Box<dyn WatcherSettingsDb>> {
+  Ok(match Database::get_tx().await? {
+    AppTransaction::Sqlite(tx) => Box::new(tx),
+    #[cfg(feature = "postgres")]
+    AppTransaction::Postgres(tx) => tx,
+  })
+}
+
+#[async_trait]
+impl WatcherSettingsDb for SqliteTransaction {
+  async fn get(&mut self, watcher: &Did) -> Loadable<WatcherSettings> {
+    get(self, watcher).await
+  }
+
+  async fn set_group_posts(&mut self, watcher: &Did, group_posts: bool) -> sqlx::Result<()> {
+    set_group_posts(self, watcher, group_posts).await
+  }
+
+  async fn set_digest(
+    &mut self,
+    watcher: &Did,
+    digest: Digest,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    set_digest(self, watcher, digest, now).await
+  }
+
+  async fn set_digest_sent_at(
+    &mut self,
+    watcher: &Did,
+    digest_sent_at: DateTime<Utc>,
+  ) -> Loadable<()> {
+    set_digest_sent_at(self, watcher, digest_sent_at).await
+  }
+
+  async fn set_timezone(&mut self, watcher: &Did, timezone: Tz) -> sqlx::Result<()> {
+    set_timezone(self, watcher, timezone).await
+  }
+
+  async fn set_quiet_hours(
+    &mut self,
+    watcher: &Did,
+    quiet_hours: Option<QuietHours>,
+  ) -> sqlx::Result<()> {
+    set_quiet_hours(self, watcher, quiet_hours).await
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use tracing::{event, Level};
+use utils::Did;
+
+use super::WatcherSettingsDb;
+use crate::{
+  watcher_settings::{Digest, QuietHours, WatcherSettings},
+  Loadable, PgTransaction,
+};
+
+/// The columns of a row of `WatcherSettings`, minus the DID.
+type Row = (
+  bool,
+  String,
+  Option<DateTime<Utc>>,
+  String,
+  Option<NaiveTime>,
+  Option<NaiveTime>,
+);
+
+/// Digests are stored as plain text, just like in Sqlite, rather than as a custom type.
+const fn digest_to_text(digest: Digest) -> &'static str {
+  match digest {
+    Digest::Off => "off",
+    Digest::Hourly => "hourly",
+    Digest::Daily => "daily",
+  }
+}
+
+fn digest_from_text(did: &str, digest: &str) -> Digest {
+  match digest {
+    "off" => Digest::Off,
+    "hourly" => Digest::Hourly,
+    "daily" => Digest::Daily,
+    _ => {
+      event!(Level::WARN, "Invalid digest saved for {did}: {digest}");
+      Digest::Off
+    }
+  }
+}
+
+#[async_trait]
+impl WatcherSettingsDb for PgTransaction {
+  async fn get(&mut self, watcher: &Did) -> Loadable<WatcherSettings> {
+    let did = &**watcher;
+    let settings: Option<Row> = sqlx::query_as(
+      r#"SELECT group_posts, digest, digest_sent_at, timezone, quiet_start, quiet_end
+         FROM "WatcherSettings" WHERE did = $1"#,
+    )
+    .bind(did)
+    .fetch_optional(&mut **self)
+    .await?;
+
+    Ok(settings.map(
+      |(group_posts, digest, digest_sent_at, timezone, quiet_start, quiet_end)| {
+        WatcherSettings {
+          group_posts,
+          digest: digest_from_text(did, &digest),
+          digest_sent_at,
+          timezone: timezone.parse().unwrap_or_else(|e| {
+            event!(Level::WARN, "Invalid timezone saved for {did}: {e}");
+            chrono_tz::UTC
+          }),
+          quiet_hours: quiet_start
+            .zip(quiet_end)
+            .map(|(start, end)| QuietHours { start, end }),
+        }
+      },
+    ))
+  }
+
+  async fn set_group_posts(&mut self, watcher: &Did, group_posts: bool) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"INSERT INTO "WatcherSettings" (did, group_posts) VALUES ($1, $2)
+         ON CONFLICT (did) DO UPDATE SET group_posts = excluded.group_posts"#,
+    )
+    .bind(&**watcher)
+    .bind(group_posts)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn set_digest(
+    &mut self,
+    watcher: &Did,
+    digest: Digest,
+    now: DateTime<Utc>,
+  ) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"INSERT INTO "WatcherSettings" (did, digest, digest_sent_at) VALUES ($1, $2, $3)
+         ON CONFLICT (did) DO UPDATE SET digest = excluded.digest, digest_sent_at = excluded.digest_sent_at"#,
+    )
+    .bind(&**watcher)
+    .bind(digest_to_text(digest))
+    .bind(now)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn set_digest_sent_at(
+    &mut self,
+    watcher: &Did,
+    digest_sent_at: DateTime<Utc>,
+  ) -> Loadable<()> {
+    let rows = sqlx::query(r#"UPDATE "WatcherSettings" SET digest_sent_at = $1 WHERE did = $2"#)
+      .bind(digest_sent_at)
+      .bind(&**watcher)
+      .execute(&mut **self)
+      .await?
+      .rows_affected();
+
+    Ok(if rows > 0 { Some(()) } else { None })
+  }
+
+  async fn set_timezone(&mut self, watcher: &Did, timezone: Tz) -> sqlx::Result<()> {
+    sqlx::query(
+      r#"INSERT INTO "WatcherSettings" (did, timezone) VALUES ($1, $2)
+         ON CONFLICT (did) DO UPDATE SET timezone = excluded.timezone"#,
+    )
+    .bind(&**watcher)
+    .bind(timezone.name())
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn set_quiet_hours(
+    &mut self,
+    watcher: &Did,
+    quiet_hours: Option<QuietHours>,
+  ) -> sqlx::Result<()> {
+    let (start, end) = quiet_hours.map(|q| (q.start, q.end)).unzip();
+    sqlx::query(
+      r#"INSERT INTO "WatcherSettings" (did, quiet_start, quiet_end) VALUES ($1, $2, $3)
+         ON CONFLICT (did) DO UPDATE SET quiet_start = excluded.quiet_start, quiet_end = excluded.quiet_end"#,
+    )
+    .bind(&**watcher)
+    .bind(start)
+    .bind(end)
+    .execute(&mut **self)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn commit(self: Box<Self>) -> sqlx::Result<()> {
+    (*self).commit().await
+  }
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{watcher_settings::Digest, SqliteTransaction};
+
+/// Saves how often a watcher wants to receive their notifications, counting the next digest
+/// from `now`, and creating their settings if they had none.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_digest(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  digest: Digest,
+  now: DateTime<Utc>,
+) -> sqlx::Result<()> {
+  let did = &**watcher;
+  sqlx::query!(
+    r#"INSERT INTO "WatcherSettings" (did, digest, digest_sent_at) VALUES ($1, $2, $3)
+       ON CONFLICT (did) DO UPDATE SET digest = excluded.digest, digest_sent_at = excluded.digest_sent_at"#,
+    did,
+    digest,
+    now
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
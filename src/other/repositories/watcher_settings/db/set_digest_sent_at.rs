@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use utils::Did;
+
+use crate::{Loadable, SqliteTransaction};
+
+/// Saves when the last digest was sent to a watcher.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_digest_sent_at(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  digest_sent_at: DateTime<Utc>,
+) -> Loadable<()> {
+  let did = &**watcher;
+  let rows = sqlx::query!(
+    r#"UPDATE "WatcherSettings" SET digest_sent_at = $1 WHERE did = $2"#,
+    digest_sent_at,
+    did
+  )
+  .execute(&mut **tx)
+  .await?
+  .rows_affected();
+
+  Ok(if rows > 0 { Some(()) } else { None })
+}
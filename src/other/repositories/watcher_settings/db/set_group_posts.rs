@@ -0,0 +1,27 @@
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Saves whether a watcher wants several new posts in a single message,
+/// creating their settings if they had none.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_group_posts(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  group_posts: bool,
+) -> sqlx::Result<()> {
+  let did = &**watcher;
+  sqlx::query!(
+    r#"INSERT INTO "WatcherSettings" (did, group_posts) VALUES ($1, $2)
+       ON CONFLICT (did) DO UPDATE SET group_posts = excluded.group_posts"#,
+    did,
+    group_posts
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,28 @@
+use utils::Did;
+
+use crate::{watcher_settings::QuietHours, SqliteTransaction};
+
+/// Saves the quiet hours of a watcher, creating their settings if they had none.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_quiet_hours(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  quiet_hours: Option<QuietHours>,
+) -> sqlx::Result<()> {
+  let did = &**watcher;
+  let (start, end) = quiet_hours.map(|q| (q.start, q.end)).unzip();
+  sqlx::query!(
+    r#"INSERT INTO "WatcherSettings" (did, quiet_start, quiet_end) VALUES ($1, $2, $3)
+       ON CONFLICT (did) DO UPDATE SET quiet_start = excluded.quiet_start, quiet_end = excluded.quiet_end"#,
+    did,
+    start,
+    end
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
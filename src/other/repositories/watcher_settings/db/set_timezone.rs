@@ -0,0 +1,28 @@
+use chrono_tz::Tz;
+use utils::Did;
+
+use crate::SqliteTransaction;
+
+/// Saves the timezone of a watcher, creating their settings if they had none.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_timezone(
+  tx: &mut SqliteTransaction,
+  watcher: &Did,
+  timezone: Tz,
+) -> sqlx::Result<()> {
+  let did = &**watcher;
+  let timezone = timezone.name();
+  sqlx::query!(
+    r#"INSERT INTO "WatcherSettings" (did, timezone) VALUES ($1, $2)
+       ON CONFLICT (did) DO UPDATE SET timezone = excluded.timezone"#,
+    did,
+    timezone
+  )
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
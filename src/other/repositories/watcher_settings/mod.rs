@@ -0,0 +1,250 @@
+//! This module contains all the re-exported interfaces for manipulating the
+//! database of watchers' settings.
+
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use tracing::{event, Level};
+use utils::Did;
+
+mod db;
+
+/// The preferences of a watcher about how they are notified.
+/// Watchers that never changed their settings get the defaults.
+#[derive(Debug, Clone, Default)]
+pub struct WatcherSettings {
+  /// Whether several new posts from the same user are notified in a single message.
+  pub group_posts: bool,
+  /// How often new posts are delivered, as a single summarised message.
+  pub digest: Digest,
+  /// When the last digest was sent, or when digests were turned on.
+  pub digest_sent_at: Option<DateTime<Utc>>,
+  /// The timezone in which the quiet hours are defined.
+  pub timezone: Tz,
+  /// A daily window during which notifications are held, to be sent once it ends.
+  pub quiet_hours: Option<QuietHours>,
+}
+impl WatcherSettings {
+  /// Returns true if `now` falls within the watcher's quiet hours.
+  #[must_use]
+  pub fn is_quiet_at(&self, now: DateTime<Utc>) -> bool {
+    self
+      .quiet_hours
+      .is_some_and(|quiet_hours| quiet_hours.contains(now.with_timezone(&self.timezone).time()))
+  }
+}
+
+/// A daily window of time, in the watcher's timezone. Wraps around midnight
+/// if it ends before it starts, e.g. from 23:00 to 08:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+  pub start: NaiveTime,
+  pub end: NaiveTime,
+}
+impl QuietHours {
+  /// Returns true if `time` falls within the window. The end itself is not included.
+  #[must_use]
+  pub fn contains(self, time: NaiveTime) -> bool {
+    if self.start <= self.end {
+      self.start <= time && time < self.end
+    } else {
+      self.start <= time || time < self.end
+    }
+  }
+}
+
+/// The cadence at which a watcher receives their notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Digest {
+  /// Notifications are sent as soon as possible.
+  #[default]
+  Off,
+  Hourly,
+  Daily,
+}
+impl Digest {
+  /// The time between two digests, or `None` if digests are off.
+  #[must_use]
+  pub const fn period(self) -> Option<TimeDelta> {
+    match self {
+      Self::Off => None,
+      Self::Hourly => Some(TimeDelta::hours(1)),
+      Self::Daily => Some(TimeDelta::days(1)),
+    }
+  }
+}
+
+/// Returns the settings of a watcher.
+/// Falls back to the defaults if the watcher has none saved, or if the query fails.
+pub async fn get(watcher: &Did) -> WatcherSettings {
+  async move {
+    let mut tx = db::begin().await?;
+    let res = tx.get(watcher).await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to get watcher settings from the database: {e}"
+    );
+  })
+  .ok()
+  .flatten()
+  .unwrap_or_default()
+}
+
+/// Saves whether a watcher wants several new posts from the same user in a single message.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_group_posts(watcher: &Did, group_posts: bool) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.set_group_posts(watcher, group_posts).await?;
+  tx.commit().await
+}
+
+/// Saves how often a watcher wants to receive their notifications.
+/// The first digest is only due one full period after this.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_digest(watcher: &Did, digest: Digest) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.set_digest(watcher, digest, Utc::now()).await?;
+  tx.commit().await
+}
+
+/// Saves the timezone in which a watcher's quiet hours are defined.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_timezone(watcher: &Did, timezone: Tz) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.set_timezone(watcher, timezone).await?;
+  tx.commit().await
+}
+
+/// Saves the quiet hours of a watcher, or turns them off if `None`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn set_quiet_hours(watcher: &Did, quiet_hours: Option<QuietHours>) -> sqlx::Result<()> {
+  let mut tx = db::begin().await?;
+  tx.set_quiet_hours(watcher, quiet_hours).await?;
+  tx.commit().await
+}
+
+/// Saves when the last digest was sent to a watcher.
+pub async fn set_digest_sent_at(watcher: &Did, digest_sent_at: DateTime<Utc>) {
+  let _ = async move {
+    let mut tx = db::begin().await?;
+    let res = tx.set_digest_sent_at(watcher, digest_sent_at).await;
+    tx.commit().await?;
+    res
+  }
+  .await
+  .map_err(|e| {
+    event!(
+      Level::WARN,
+      "Failed to save digest time to the database: {e}"
+    );
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{DateTime, NaiveTime, Utc};
+  use chrono_tz::Tz;
+
+  use super::{QuietHours, WatcherSettings};
+
+  fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").expect("valid time")
+  }
+
+  fn window(start: &str, end: &str) -> QuietHours {
+    QuietHours {
+      start: time(start),
+      end: time(end),
+    }
+  }
+
+  #[test]
+  fn contains_times_within_the_window() {
+    let cases = [
+      // Same day
+      (("13:00", "15:00"), "12:59", false),
+      (("13:00", "15:00"), "13:00", true),
+      (("13:00", "15:00"), "14:30", true),
+      (("13:00", "15:00"), "15:00", false),
+      // Wrapping past midnight
+      (("23:00", "08:00"), "22:59", false),
+      (("23:00", "08:00"), "23:00", true),
+      (("23:00", "08:00"), "23:59", true),
+      (("23:00", "08:00"), "00:00", true),
+      (("23:00", "08:00"), "07:59", true),
+      (("23:00", "08:00"), "08:00", false),
+      (("23:00", "08:00"), "12:00", false),
+      // Ending at midnight
+      (("22:00", "00:00"), "23:59", true),
+      (("22:00", "00:00"), "00:00", false),
+    ];
+    for ((start, end), at, expected) in cases {
+      assert_eq!(
+        window(start, end).contains(time(at)),
+        expected,
+        "{start}-{end} at {at}"
+      );
+    }
+  }
+
+  #[test]
+  fn is_quiet_at_converts_to_the_watchers_timezone() {
+    let settings = |timezone: Tz| WatcherSettings {
+      timezone,
+      quiet_hours: Some(window("23:00", "08:00")),
+      ..Default::default()
+    };
+    let cases = [
+      // No quiet hours in UTC until 23:00
+      (Tz::UTC, "2026-01-15T22:30:00Z", false),
+      // Berlin is UTC+1 in winter...
+      (Tz::Europe__Berlin, "2026-01-15T22:30:00Z", true),
+      (Tz::Europe__Berlin, "2026-01-16T06:30:00Z", true),
+      (Tz::Europe__Berlin, "2026-01-16T07:30:00Z", false),
+      // ...and UTC+2 in summer
+      (Tz::Europe__Berlin, "2026-07-01T06:30:00Z", false),
+      (Tz::Europe__Berlin, "2026-07-01T21:30:00Z", true),
+      // The night DST starts, 02:00 becomes 03:00
+      (Tz::Europe__Berlin, "2026-03-28T22:30:00Z", true),
+      (Tz::Europe__Berlin, "2026-03-29T05:30:00Z", true),
+      (Tz::Europe__Berlin, "2026-03-29T06:30:00Z", false),
+      // The night DST ends, 03:00 becomes 02:00
+      (Tz::Europe__Berlin, "2026-10-25T06:30:00Z", true),
+      (Tz::Europe__Berlin, "2026-10-25T07:30:00Z", false),
+      // Behind UTC
+      (Tz::America__Sao_Paulo, "2026-01-16T02:30:00Z", true),
+      (Tz::America__Sao_Paulo, "2026-01-16T11:30:00Z", false),
+    ];
+    for (timezone, now, expected) in cases {
+      let now: DateTime<Utc> = now.parse().expect("valid datetime");
+      assert_eq!(
+        settings(timezone).is_quiet_at(now),
+        expected,
+        "{timezone} at {now}"
+      );
+    }
+  }
+
+  #[test]
+  fn is_never_quiet_without_quiet_hours() {
+    let now = "2026-01-16T02:00:00Z".parse().expect("valid datetime");
+    assert!(!WatcherSettings::default().is_quiet_at(now));
+  }
+}
@@ -0,0 +1,57 @@
+//! # `Digest` command.
+//!
+//! Implements the `Command` trait and the `Parseable` trait.
+//! Is parsed by reading the first argument, which must be either `hourly`, `daily` or `off`.
+//!
+//! Saves how often the sender wants to receive their notifications. When on, new posts
+//! are queued and sent as a single summarised message at the chosen cadence.
+
+use atrium_api::{app::bsky::richtext::facet::Main, types::string::Did};
+use repositories::watcher_settings::{self, Digest as Cadence};
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
+
+#[derive(Debug)]
+pub enum Digest {
+  ParseSuccess(Cadence),
+  ParseFail,
+}
+impl Parseable for Digest {
+  async fn parse(args: &[&str], _: Option<Vec<Main>>) -> Result<Self> {
+    let res = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+      Some("hourly") => Self::ParseSuccess(Cadence::Hourly),
+      Some("daily") => Self::ParseSuccess(Cadence::Daily),
+      Some("off") => Self::ParseSuccess(Cadence::Off),
+      _ => Self::ParseFail,
+    };
+    Ok(res)
+  }
+}
+impl Command for Digest {
+  fn process(self: Box<Self>, sender_did: Did) -> PinnedFut<Result<String>> {
+    Box::pin(async move {
+      match *self {
+        Self::ParseSuccess(cadence) => {
+          let watcher = String::from(sender_did).into();
+          if let Err(e) = watcher_settings::set_digest(&watcher, cadence).await {
+            event!(Level::WARN, "Failed to save watcher settings: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
+
+          Ok(
+            match cadence {
+              Cadence::Off => "Digests are now off. New posts will be sent as they come.",
+              Cadence::Hourly => "New posts will now be sent to you as an hourly digest.",
+              Cadence::Daily => "New posts will now be sent to you as a daily digest.",
+            }
+            .to_string(),
+          )
+        }
+        Self::ParseFail => {
+          Ok("Please use either `!digest hourly`, `!digest daily` or `!digest off`.".to_string())
+        }
+      }
+    })
+  }
+}
@@ -0,0 +1,53 @@
+//! # `GroupPosts` command.
+//!
+//! Implements the `Command` trait and the `Parseable` trait.
+//! Is parsed by reading the first argument, which must be either `on` or `off`.
+//!
+//! Saves whether the sender wants several new posts from the same user to be
+//! notified in a single message, instead of one message per post.
+
+use atrium_api::{app::bsky::richtext::facet::Main, types::string::Did};
+use repositories::watcher_settings;
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
+
+#[derive(Debug)]
+pub enum GroupPosts {
+  ParseSuccess(bool),
+  ParseFail,
+}
+impl Parseable for GroupPosts {
+  async fn parse(args: &[&str], _: Option<Vec<Main>>) -> Result<Self> {
+    let res = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+      Some("on") => Self::ParseSuccess(true),
+      Some("off") => Self::ParseSuccess(false),
+      _ => Self::ParseFail,
+    };
+    Ok(res)
+  }
+}
+impl Command for GroupPosts {
+  fn process(self: Box<Self>, sender_did: Did) -> PinnedFut<Result<String>> {
+    Box::pin(async move {
+      match *self {
+        Self::ParseSuccess(group_posts) => {
+          let watcher = String::from(sender_did).into();
+          if let Err(e) = watcher_settings::set_group_posts(&watcher, group_posts).await {
+            event!(Level::WARN, "Failed to save watcher settings: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
+
+          Ok(if group_posts {
+            "Several new posts from the same user will now be sent in a single message.".to_string()
+          } else {
+            "Each new post will now be sent in its own message.".to_string()
+          })
+        }
+        Self::ParseFail => {
+          Ok("Please use either `!group_posts on` or `!group_posts off`.".to_string())
+        }
+      }
+    })
+  }
+}
@@ -17,9 +17,13 @@ impl Command for Help {
       Ok(
         "\
 Available commands:
-- `!watch @user_1 @user_2 (...)`
+- `!watch [--replies] @user_1 @user_2 (...)`
 - `!unwatch @user_1 @user_2 (...)`
 - `!list_watched`
+- `!group_posts on|off`
+- `!digest hourly|daily|off`
+- `!timezone Europe/Berlin`
+- `!quiet 23:00-08:00` or `!quiet off`
 - `!help`\
         "
         .to_string(),
@@ -3,8 +3,8 @@
 //! Implements the `Command` trait. Does not implement the `Parseable` trait,
 //! as there is no relevant information in the command that is used here.
 //!
-//! Fetches all the users that the sender is watching and returns a message
-//! with all the handles of the users.
+//! Fetches all the users that the sender is watching from the database and returns
+//! a message with all the handles of the users.
 
 use std::sync::Arc;
 
@@ -22,6 +22,7 @@ impl Command for ListWatched {
     Box::pin(async move {
       let watched: Vec<_> = watched_user::get_watched_by(&Arc::from(String::from(sender_did)))
         .await
+        .map_err(|e| anyhow::anyhow!(e))?
         .into_iter()
         .map(|d| d.parse::<AtIdentifier>().unwrap())
         .collect();
@@ -1,6 +1,10 @@
+mod digest;
+mod group_posts;
 mod help;
 mod invalid;
 mod list_watched;
+mod quiet;
+mod timezone;
 mod unknown;
 mod unwatch;
 mod watch;
@@ -14,10 +18,14 @@ use atrium_api::{
     Object,
   },
 };
+use digest::Digest;
+use group_posts::GroupPosts;
 use help::Help;
 use invalid::Invalid;
 use list_watched::ListWatched;
+use quiet::Quiet;
 use std::fmt::Debug;
+use timezone::Timezone;
 use tracing::{event, Level};
 use unknown::Unknown;
 use unwatch::Unwatch;
@@ -27,11 +35,20 @@ use watch::Watch;
 pub type Result<T> = core::result::Result<T, bsky::Error<anyhow::Error>>;
 pub type PinnedFut<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
 
+/// Reply sent when changes to the watchlist or settings could not be saved.
+const SAVE_FAILED: &str =
+  "Sorry, I couldn't save your changes, so nothing changed. Please try again later.";
+
 /// A trait for commands that can be processed by the bot.
 pub trait Command: Debug {
   /// This implementation should return a boxed future, that will be handled.
   /// That future should return a message that will be sent to the user.
   fn process(self: Box<Self>, sender_id: Did) -> PinnedFut<Result<String>>;
+  /// The name of the command in the metrics, i.e. the name of its type.
+  fn name(&self) -> &'static str {
+    let path = std::any::type_name::<Self>();
+    path.rsplit("::").next().unwrap_or(path)
+  }
   /// This implementation should return a boxed version of the command,
   /// so that it can be dynamically dispatched.
   fn box_dyn(self) -> Box<dyn Command + Send>
@@ -41,10 +58,11 @@ pub trait Command: Debug {
     Box::new(self)
   }
 }
-/// A trait for commands that can be parsed from facets.
+/// A trait for commands that can be parsed from their arguments and facets.
 pub trait Parseable: Command {
-  /// This implementation should parse the facets and return a `Result` with the parsed command.
-  async fn parse(facets: Option<Vec<Main>>) -> Result<Self>
+  /// This implementation should parse the arguments (the words following the command)
+  /// and facets, and return a `Result` with the parsed command.
+  async fn parse(args: &[&str], facets: Option<Vec<Main>>) -> Result<Self>
   where
     Self: Sized;
 }
@@ -70,12 +88,17 @@ pub async fn parse(
   let mut parts = text.split_whitespace();
   #[expect(clippy::unwrap_used)] // Checked above
   let command = parts.next().unwrap().to_lowercase();
+  let args: Vec<_> = parts.collect();
 
   let res = match command.as_str() {
     "!help" => Help.box_dyn(),
-    "!watch" => Watch::parse(facets).await?.box_dyn(),
-    "!unwatch" => Unwatch::parse(facets).await?.box_dyn(),
+    "!watch" => Watch::parse(&args, facets).await?.box_dyn(),
+    "!unwatch" => Unwatch::parse(&args, facets).await?.box_dyn(),
     "!list_watched" => ListWatched.box_dyn(),
+    "!group_posts" => GroupPosts::parse(&args, facets).await?.box_dyn(),
+    "!digest" => Digest::parse(&args, facets).await?.box_dyn(),
+    "!timezone" => Timezone::parse(&args, facets).await?.box_dyn(),
+    "!quiet" => Quiet::parse(&args, facets).await?.box_dyn(),
     _ => Unknown.box_dyn(),
   };
   Ok(res)
@@ -0,0 +1,120 @@
+//! # `Quiet` command.
+//!
+//! Implements the `Command` trait and the `Parseable` trait.
+//! Is parsed by reading the first argument, which must be either a window of time
+//! such as `23:00-08:00`, or `off`.
+//!
+//! Saves the sender's quiet hours, in their timezone. Notifications that arrive during
+//! quiet hours are held and sent as soon as they end.
+
+use atrium_api::{app::bsky::richtext::facet::Main, types::string::Did};
+use chrono::NaiveTime;
+use repositories::watcher_settings::{self, QuietHours};
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
+
+#[derive(Debug)]
+pub enum Quiet {
+  ParseSuccess(Option<QuietHours>),
+  ParseFail,
+}
+impl Parseable for Quiet {
+  async fn parse(args: &[&str], _: Option<Vec<Main>>) -> Result<Self> {
+    let Some(arg) = args.first() else {
+      return Ok(Self::ParseFail);
+    };
+    if arg.eq_ignore_ascii_case("off") {
+      return Ok(Self::ParseSuccess(None));
+    }
+
+    let window = arg.split_once('-').and_then(|(start, end)| {
+      let start = NaiveTime::parse_from_str(start, "%H:%M").ok()?;
+      let end = NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+      Some(QuietHours { start, end })
+    });
+    let res = match window {
+      Some(window) if window.start != window.end => Self::ParseSuccess(Some(window)),
+      _ => Self::ParseFail,
+    };
+    Ok(res)
+  }
+}
+impl Command for Quiet {
+  fn process(self: Box<Self>, sender_did: Did) -> PinnedFut<Result<String>> {
+    Box::pin(async move {
+      match *self {
+        Self::ParseSuccess(quiet_hours) => {
+          let watcher = String::from(sender_did).into();
+          if let Err(e) = watcher_settings::set_quiet_hours(&watcher, quiet_hours).await {
+            event!(Level::WARN, "Failed to save watcher settings: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
+
+          Ok(match quiet_hours {
+            Some(QuietHours { start, end }) => format!(
+              "Notifications will now be held from {} to {}, in your timezone (see `!timezone`).",
+              start.format("%H:%M"),
+              end.format("%H:%M")
+            ),
+            None => "Quiet hours are now off.".to_string(),
+          })
+        }
+        Self::ParseFail => Ok(
+          "Please use either `!quiet HH:MM-HH:MM` (e.g. `!quiet 23:00-08:00`) or `!quiet off`."
+            .to_string(),
+        ),
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::NaiveTime;
+  use repositories::watcher_settings::QuietHours;
+
+  use super::{Parseable, Quiet};
+
+  fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").expect("valid time")
+  }
+
+  #[tokio::test]
+  async fn parses_windows_and_off() {
+    let cases = [
+      ("23:00-08:00", Some(Some(("23:00", "08:00")))),
+      ("09:30-17:00", Some(Some(("09:30", "17:00")))),
+      ("off", Some(None)),
+      ("OFF", Some(None)),
+      ("08:00-08:00", None),
+      ("23:00", None),
+      ("23:00-", None),
+      ("24:00-08:00", None),
+      ("11pm-8am", None),
+      ("23:00-08:00-09:00", None),
+    ];
+    for (arg, expected) in cases {
+      let expected = expected.map(|window| {
+        window.map(|(start, end)| QuietHours {
+          start: time(start),
+          end: time(end),
+        })
+      });
+      let parsed = match Quiet::parse(&[arg], None)
+        .await
+        .expect("parse doesn't fail")
+      {
+        Quiet::ParseSuccess(quiet_hours) => Some(quiet_hours),
+        Quiet::ParseFail => None,
+      };
+      assert_eq!(parsed, expected, "!quiet {arg}");
+    }
+  }
+
+  #[tokio::test]
+  async fn fails_without_arguments() {
+    let parsed = Quiet::parse(&[], None).await.expect("parse doesn't fail");
+    assert!(matches!(parsed, Quiet::ParseFail));
+  }
+}
@@ -0,0 +1,81 @@
+//! # `Timezone` command.
+//!
+//! Implements the `Command` trait and the `Parseable` trait.
+//! Is parsed by reading the first argument as an IANA timezone name, e.g. `Europe/Berlin`.
+//!
+//! Saves the timezone in which the sender's quiet hours are defined.
+
+use atrium_api::{app::bsky::richtext::facet::Main, types::string::Did};
+use chrono_tz::Tz;
+use repositories::watcher_settings;
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
+
+#[derive(Debug)]
+pub enum Timezone {
+  ParseSuccess(Tz),
+  ParseFail,
+}
+impl Parseable for Timezone {
+  async fn parse(args: &[&str], _: Option<Vec<Main>>) -> Result<Self> {
+    let res = args
+      .first()
+      .and_then(|arg| arg.parse().ok())
+      .map_or(Self::ParseFail, Self::ParseSuccess);
+    Ok(res)
+  }
+}
+impl Command for Timezone {
+  fn process(self: Box<Self>, sender_did: Did) -> PinnedFut<Result<String>> {
+    Box::pin(async move {
+      match *self {
+        Self::ParseSuccess(timezone) => {
+          let watcher = String::from(sender_did).into();
+          if let Err(e) = watcher_settings::set_timezone(&watcher, timezone).await {
+            event!(Level::WARN, "Failed to save watcher settings: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
+
+          Ok(format!("Your timezone is now {}.", timezone.name()))
+        }
+        Self::ParseFail => {
+          Ok("Please provide a valid timezone name, such as `!timezone Europe/Berlin`.".to_string())
+        }
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono_tz::Tz;
+
+  use super::{Parseable, Timezone};
+
+  #[tokio::test]
+  async fn parses_iana_names() {
+    let cases = [
+      ("Europe/Berlin", Some(Tz::Europe__Berlin)),
+      ("America/Sao_Paulo", Some(Tz::America__Sao_Paulo)),
+      ("UTC", Some(Tz::UTC)),
+      ("Mars/Olympus_Mons", None),
+      ("+02:00", None),
+      ("", None),
+    ];
+    for (arg, expected) in cases {
+      let parsed = match Timezone::parse(&[arg], None)
+        .await
+        .expect("parse doesn't fail")
+      {
+        Timezone::ParseSuccess(timezone) => Some(timezone),
+        Timezone::ParseFail => None,
+      };
+      assert_eq!(parsed, expected, "!timezone {arg}");
+    }
+    let parsed = Timezone::parse(&[], None)
+      .await
+      .expect("parse doesn't fail");
+    assert!(matches!(parsed, Timezone::ParseFail));
+  }
+}
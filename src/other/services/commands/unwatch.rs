@@ -16,7 +16,9 @@ use atrium_api::{
 
 use crate::{resolve_dids_and_handles, unwatch_users};
 
-use super::{Command, Parseable, PinnedFut, Result};
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
 
 #[derive(Debug)]
 pub enum Unwatch {
@@ -24,10 +26,9 @@ pub enum Unwatch {
   ParseFail,
 }
 impl Parseable for Unwatch {
-  async fn parse(facets: Option<Vec<Main>>) -> Result<Self> {
-    let facets = match facets {
-      None => return Ok(Self::ParseFail),
-      Some(facets) => facets,
+  async fn parse(_: &[&str], facets: Option<Vec<Main>>) -> Result<Self> {
+    let Some(facets) = facets else {
+      return Ok(Self::ParseFail);
     };
 
     let at_ids = super::extract_mentions(facets);
@@ -46,7 +47,10 @@ impl Command for Unwatch {
     Box::pin(async move {
       match *self {
         Self::ParseSuccess(dids, handles) => {
-          unwatch_users::act(sender_did, dids).await;
+          if let Err(e) = unwatch_users::act(sender_did, dids).await {
+            event!(Level::WARN, "Failed to save unwatched users: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
 
           Ok(
             handles
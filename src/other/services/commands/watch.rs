@@ -3,8 +3,9 @@
 //! Implements the `Command` trait and the `Parseable` trait.
 //! Is parsed by extracting the mentions from the facets found in the message,
 //! and resolving all of them to corresponding DIDs and Handles.
+//! If the `--replies` flag is passed, the sender will also be notified about replies.
 //!
-//! - DIDs are used for unwatching users in the `Command` trait.
+//! - DIDs are used for watching users in the `Command` trait.
 //! - Handles are used for notifying the user about the users that were successfully watched.
 
 use std::collections::HashSet;
@@ -16,18 +17,21 @@ use atrium_api::{
 
 use crate::{resolve_dids_and_handles, watch_new_users};
 
-use super::{Command, Parseable, PinnedFut, Result};
+use tracing::{event, Level};
+
+use super::{Command, Parseable, PinnedFut, Result, SAVE_FAILED};
 
 #[derive(Debug)]
 pub enum Watch {
-  ParseSuccess(HashSet<Did>, HashSet<Handle>),
+  ParseSuccess(HashSet<Did>, HashSet<Handle>, bool),
   ParseFail,
 }
 impl Parseable for Watch {
-  async fn parse(facets: Option<Vec<Main>>) -> Result<Self> {
-    let facets = match facets {
-      None => return Ok(Self::ParseFail),
-      Some(facets) => facets,
+  async fn parse(args: &[&str], facets: Option<Vec<Main>>) -> Result<Self> {
+    let with_replies = args.contains(&"--replies");
+
+    let Some(facets) = facets else {
+      return Ok(Self::ParseFail);
     };
 
     let at_ids = super::extract_mentions(facets);
@@ -38,23 +42,27 @@ impl Parseable for Watch {
     let (dids, handles) = resolve_dids_and_handles::act(at_ids)
       .await
       .map_err(|e| anyhow::anyhow!(e))?;
-    Ok(Self::ParseSuccess(dids, handles))
+    Ok(Self::ParseSuccess(dids, handles, with_replies))
   }
 }
 impl Command for Watch {
   fn process(self: Box<Self>, sender_did: Did) -> PinnedFut<Result<String>> {
     Box::pin(async move {
       match *self {
-        Self::ParseSuccess(dids, handles) => {
-          watch_new_users::act(sender_did, dids).await;
-
-          Ok(
-            handles
-              .into_iter()
-              .fold("Now watching users:".to_string(), |acc, handle| {
-                format!("{}\n- @{}", acc, handle.as_ref())
-              }),
-          )
+        Self::ParseSuccess(dids, handles, with_replies) => {
+          if let Err(e) = watch_new_users::act(sender_did, dids, with_replies).await {
+            event!(Level::WARN, "Failed to save watched users: {e}");
+            return Ok(SAVE_FAILED.to_string());
+          }
+
+          let header = if with_replies {
+            "Now watching users, including their replies:"
+          } else {
+            "Now watching users:"
+          };
+          Ok(handles.into_iter().fold(header.to_string(), |acc, handle| {
+            format!("{}\n- @{}", acc, handle.as_ref())
+          }))
         }
         Self::ParseFail => Ok("Please make sure to mention at least one user.".to_string()),
       }
@@ -0,0 +1,89 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+
+lazy_static! {
+  static ref JOBS: Mutex<BTreeMap<Job, JobStatus>> = Mutex::default();
+}
+
+/// The long-running jobs whose state is reported by the health checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Job {
+  CommandListener,
+  CommandIssuer,
+  UserWatcher,
+  JetstreamListener,
+  NotificationSender,
+  DigestSender,
+  WatchlistReloader,
+}
+impl Job {
+  #[must_use]
+  pub const fn name(self) -> &'static str {
+    match self {
+      Self::CommandListener => "command_listener",
+      Self::CommandIssuer => "command_issuer",
+      Self::UserWatcher => "user_watcher",
+      Self::JetstreamListener => "jetstream_listener",
+      Self::NotificationSender => "notification_sender",
+      Self::DigestSender => "digest_sender",
+      Self::WatchlistReloader => "watchlist_reloader",
+    }
+  }
+
+  /// Marks the job as running until the returned guard is dropped, which happens when the
+  /// job returns, panics or is cancelled.
+  pub(crate) fn running(self) -> Running {
+    set(self, JobState::Running);
+    Running(self)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+  Running,
+  Stopped,
+}
+impl JobState {
+  #[must_use]
+  pub const fn name(self) -> &'static str {
+    match self {
+      Self::Running => "running",
+      Self::Stopped => "stopped",
+    }
+  }
+}
+
+/// The current state of a job, and since when it's been in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobStatus {
+  pub state: JobState,
+  pub since: DateTime<Utc>,
+}
+
+/// Marks a job as stopped once dropped.
+pub(crate) struct Running(Job);
+impl Drop for Running {
+  fn drop(&mut self) {
+    set(self.0, JobState::Stopped);
+  }
+}
+
+fn set(job: Job, state: JobState) {
+  let status = JobStatus {
+    state,
+    since: Utc::now(),
+  };
+  #[expect(clippy::unwrap_used)] // Only poisoned if another thread panicked
+  JOBS.lock().unwrap().insert(job, status);
+}
+
+/// Gets the status of every job that has been started so far.
+#[must_use]
+#[expect(clippy::missing_panics_doc)] // False positive because of unwrap
+pub fn jobs() -> Vec<(Job, JobStatus)> {
+  #[expect(clippy::unwrap_used)] // Only poisoned if another thread panicked
+  let jobs = JOBS.lock().unwrap();
+  jobs.iter().map(|(job, status)| (*job, *status)).collect()
+}
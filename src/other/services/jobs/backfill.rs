@@ -0,0 +1,88 @@
+use std::cmp;
+
+use atrium_api::types::string::AtIdentifier;
+use bsky::get_posts_since;
+use chrono::{DateTime, TimeDelta, Utc};
+use environment::owned_var_or;
+use repositories::watched_user;
+use tracing::{event, Level};
+use utils::Did;
+
+use crate::{
+  notify::{self, watcher::Notification},
+  user_unwatched::{self, Reason},
+};
+
+static DEFAULT_MAX_BACKFILL_HOURS: i64 = 24; // 24 Hours
+
+/// Method for recovering the posts made while the bot was down.
+///
+/// For every watched user, fetches all posts and replies made after the last one their watchers
+/// were notified about, up until `until`, and notifies the watchers about them.
+/// Posts older than `MAX_BACKFILL_HOURS` are not recovered.
+pub async fn begin(until: DateTime<Utc>) {
+  let max_backfill_hours = owned_var_or("MAX_BACKFILL_HOURS", DEFAULT_MAX_BACKFILL_HOURS);
+  let window_start = until - TimeDelta::hours(max_backfill_hours);
+
+  event!(Level::INFO, "Backfilling posts made while offline...");
+
+  for watched_did in watched_user::get_watching().await {
+    let Some(last_post_at) = watched_user::get_last_post_at(&watched_did).await else {
+      continue;
+    };
+    let since = cmp::max(last_post_at, window_start);
+    if since < until {
+      backfill(watched_did, since, until).await;
+    }
+  }
+
+  event!(Level::INFO, "Finished backfilling posts.");
+}
+
+/// Notifies the watchers of a user about each post and reply made by them in a time window.
+#[expect(clippy::cognitive_complexity)]
+async fn backfill(watched_did: Did, since: DateTime<Utc>, until: DateTime<Utc>) {
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let actor = watched_did.parse::<AtIdentifier>().unwrap();
+  match get_posts_since::act(actor, since, Some(until)).await {
+    Err(bsky::Error::Api | bsky::Error::BskyBug) => {
+      event!(
+        Level::WARN,
+        "(Notice) Failed to backfill posts for {watched_did}. Posts made while offline will be skipped."
+      );
+    }
+    Err(bsky::Error::Other(get_posts_since::Error::UserOptedOut)) => {
+      event!(
+        Level::INFO,
+        "{watched_did} has opted out of the watchlist. Will stop watching."
+      );
+      user_unwatched::handle(watched_did, Reason::OptedOut).await;
+    }
+    Err(bsky::Error::Other(
+      get_posts_since::Error::AccountDeactivated | get_posts_since::Error::AccountTakedown,
+    )) => {
+      event!(
+        Level::INFO,
+        "{watched_did}'s account is unavailable for now. Skipping backfill."
+      );
+    }
+    Err(bsky::Error::Other(get_posts_since::Error::AccountNotFound)) => {
+      event!(
+        Level::INFO,
+        "{watched_did}'s account no longer exists. Will stop watching."
+      );
+      user_unwatched::handle(watched_did, Reason::Gone).await;
+    }
+    Ok(posts) => {
+      if let Some(last_post) = posts.last() {
+        event!(
+          Level::INFO,
+          "Found {} posts from {watched_did} made while offline.",
+          posts.len()
+        );
+        watched_user::set_last_post_at(&watched_did, last_post.indexed_at).await;
+        notify::watcher::many(watched_did, None, Notification::Posts(posts)).await;
+      }
+    }
+  }
+}
@@ -1,40 +1,67 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
-use crate::{
-  jobs::command_listener,
-  pending_messages::{self, PENDING_MESSAGES},
-};
+use crate::{health::Job, jobs::command_listener, pending_messages};
 
+use chrono::{TimeDelta, Utc};
+use repositories::command_inbox::{self, InboxCommand};
 use tokio::time::sleep;
 use tracing::{event, Level};
 
-/// Method for handling all the new commands that were previously cached by
-/// the bot (check `command_listener`).
-/// Will fetch the last unread convos from time to time (`WATCH_DELAY`),
-/// and then handle each message accordingly.
+/// How long finished commands are kept in the inbox, so that receiving them again is ignored.
+static INBOX_RETENTION: i64 = 7; // 7 Days
+/// How many iterations to wait between each pruning of the inbox.
+static PRUNE_EVERY: u32 = 720;
+/// How long the commands of a convo are kept from being picked up again while they're being
+/// processed, be it by this instance or by others sharing the database.
+static CLAIM_LEASE: i64 = 15; // 15 Minutes
+
+/// Method for handling all the new commands that were previously saved to the
+/// command inbox by the bot (check `command_listener`).
+///
+/// Will fetch the pending commands from time to time (`WATCH_DELAY`),
+/// and then handle each convo's commands in the order they were sent, while
+/// different convos are handled concurrently. A convo's commands are claimed until
+/// they're handled, so that they're not picked up twice. Since commands are only marked as
+/// done once handled, any that were interrupted, e.g. by a restart, are picked
+/// up again, and the ones that failed are retried a few times.
 /// Command failure will be logged, but the bot will not notify the user about it,
 /// after all, if the command failed it's because it wasn't able to notify the user
 /// to begin with.
 ///
-/// Note: This job will only ever stop if `command_listener` job stop, given that
-/// they're both being used in a `tokio::select!` block.
+/// Note: This job never stops on its own, but is still restarted if it crashes
+/// (check `supervisor`).
 #[expect(clippy::missing_panics_doc)] // False positive because of unwrap
 pub async fn begin() {
+  let _running = Job::CommandIssuer.running();
   event!(Level::INFO, "Now handling pending messages.");
 
+  let mut iteration = 0;
   loop {
-    PENDING_MESSAGES
-      .write()
+    if iteration % PRUNE_EVERY == 0 {
+      prune().await;
+    }
+    iteration = iteration.wrapping_add(1);
+
+    let pending = command_inbox::claim_pending(TimeDelta::minutes(CLAIM_LEASE))
       .await
-      .drain()
-      .map(|(k, v)| {
-        tokio::spawn(async {
-          pending_messages::process(k, v)
-            .await
-            .map_err(|e| event!(Level::WARN, "(Notice) Failed to handle pending message. User will have to reissue command. Error: {e}"))
-        })
-      })
-      .for_each(drop);
+      .unwrap_or_else(|e| {
+        event!(Level::WARN, "Failed to get pending commands: {e}");
+        Vec::new()
+      });
+    let mut by_convo: HashMap<String, Vec<InboxCommand>> = HashMap::new();
+    for command in pending {
+      by_convo
+        .entry(command.convo_id.clone())
+        .or_default()
+        .push(command);
+    }
+    for commands in by_convo.into_values() {
+      tokio::spawn(async move {
+        let message_ids: Vec<String> = commands.iter().map(|c| c.message_id.clone()).collect();
+        process_in_order(commands).await;
+        command_inbox::release(&message_ids).await;
+      });
+    }
 
     #[expect(clippy::unwrap_used)] // Constant
     sleep(Duration::from_secs(
@@ -43,3 +70,27 @@ pub async fn begin() {
     .await;
   }
 }
+
+/// Processes the commands of a convo one at a time, in order. Stops at the first one
+/// that fails, so that the ones after it aren't processed before it's retried.
+async fn process_in_order(commands: Vec<InboxCommand>) {
+  for command in commands {
+    let Err(e) = pending_messages::process(command).await else {
+      continue;
+    };
+    event!(
+      Level::WARN,
+      "(Notice) Failed to handle pending message. Will be retried. Error: {e}"
+    );
+    break;
+  }
+}
+
+/// Removes the commands that finished long ago from the inbox.
+async fn prune() {
+  match command_inbox::prune(Utc::now() - TimeDelta::days(INBOX_RETENTION)).await {
+    Ok(0) => {}
+    Ok(pruned) => event!(Level::DEBUG, "Pruned {pruned} old commands from the inbox."),
+    Err(e) => event!(Level::WARN, "Failed to prune the command inbox: {e}"),
+  }
+}
@@ -6,11 +6,12 @@ use tokio::time::sleep;
 use tracing::{event, Level};
 use utils::handle_api_failure;
 
-use crate::unanswered_convos;
+use crate::{health::Job, unanswered_convos};
 
 pub static WATCH_DELAY: i64 = 2; // 2 Seconds
 
 /// Method for listening for new commands from users.
+///
 /// Will fetch the last unread convos from time to time (`WATCH_DELAY`),
 /// and then handle each message accordingly.
 /// Has a basic compensation mechanism that tries to, on average and as much as possible,
@@ -18,15 +19,15 @@ pub static WATCH_DELAY: i64 = 2; // 2 Seconds
 /// Also has a mechanism to handle persistent API failures, cancelling the job if the
 /// error appears to be unrecoverable, logging the error.
 ///
-/// Note: This job failing means the `command_issuer` job will also stop, given that
-/// they're both being used in a `tokio::select!` block.
+/// Note: Once cancelled, this job is restarted after a while (check `supervisor`), so
+/// the commands sent in the meantime are picked up late rather than missed.
 /// Also important to note that if the user sends multiple messages in a row, they will
-/// be handled in order, but the bot will not wait for the previous commands to be issued,
-/// and will ignore any previous messages if the user sends a new one. This is to prevent
-/// the bot from being stuck on a single user's messages (`DDoS`).
+/// all be queued and handled in order, but only up to `MAX_QUEUED_COMMANDS` at once.
+/// This is to prevent the bot from being stuck on a single user's messages (`DDoS`).
 #[expect(clippy::cognitive_complexity)]
 #[expect(clippy::missing_panics_doc)] // False positive because of unwrap
 pub async fn begin() {
+  let _running = Job::CommandListener.running();
   event!(Level::INFO, "Now listening to user commands.");
 
   let mut failures_in_a_row = 0;
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use repositories::{notification_outbox, watcher_settings};
+use tokio::time::sleep;
+use tracing::{event, Level};
+use utils::Did;
+
+use crate::{health::Job, notify::watcher::ENQUEUED};
+
+static FLUSH_DELAY: u64 = 60; // 60 Seconds
+
+/// Method for sending digests to the watchers that prefer them.
+///
+/// Will check the notifications held in the outbox for every watcher from time to time
+/// (`FLUSH_DELAY`), releasing them once their digest is due, to be delivered as a single
+/// message by the notification sender (check `notification_sender`).
+/// Watchers that turned digests off get whatever was left held right away, and
+/// notifications held during quiet hours are sent as soon as they end.
+pub async fn begin() {
+  let _running = Job::DigestSender.running();
+  event!(Level::INFO, "Now sending digests.");
+
+  loop {
+    for watcher in notification_outbox::get_holding_watchers().await {
+      flush(watcher).await;
+    }
+    sleep(Duration::from_secs(FLUSH_DELAY)).await;
+  }
+}
+
+/// Releases a watcher's held notifications, if their digest is due and they're outside their
+/// quiet hours.
+async fn flush(watcher: Did) {
+  let settings = watcher_settings::get(&watcher).await;
+  let now = Utc::now();
+  if settings.is_quiet_at(now) {
+    return;
+  }
+  if let (Some(period), Some(sent_at)) = (settings.digest.period(), settings.digest_sent_at) {
+    if now - sent_at < period {
+      return;
+    }
+  }
+
+  match notification_outbox::release_held(&watcher).await {
+    Ok(0) => {}
+    Ok(released) => {
+      event!(
+        Level::DEBUG,
+        "Released {released} held notifications for {watcher}'s digest."
+      );
+      watcher_settings::set_digest_sent_at(&watcher, now).await;
+      ENQUEUED.notify_one();
+    }
+    Err(e) => event!(
+      Level::WARN,
+      "(Notice) Failed to release {watcher}'s held notifications: {e}"
+    ),
+  }
+}
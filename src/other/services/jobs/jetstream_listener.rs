@@ -0,0 +1,288 @@
+use std::{
+  collections::HashSet,
+  sync::atomic::{AtomicU64, Ordering},
+  time::Duration,
+};
+
+use bsky::{get_profile, FeedPost};
+use chrono::{DateTime, Utc};
+use environment::owned_var_or_else;
+use jetstream::{AccountStatus, PostCreated, Subscription, Update};
+use repositories::watched_user::{self, Profile};
+use tokio::time::{interval, interval_at, Instant};
+use tracing::{event, Level};
+use utils::{handle_api_failure, Did};
+
+use crate::{
+  health::Job,
+  notify::{self, watcher::Notification},
+  profile_changed, user_suspended,
+  user_unwatched::{self, Reason},
+};
+
+static DEFAULT_JETSTREAM_URL: &str = "wss://jetstream2.us-east.bsky.network/subscribe";
+static REFRESH_DELAY: u64 = 5; // 5 Seconds
+/// How often suspended users are probed, in case their account was reactivated while the bot
+/// wasn't listening.
+static PROBE_DELAY: u64 = 60 * 60; // 1 Hour
+/// The time of the last received event, kept across restarts of the job.
+static LAST_CURSOR: AtomicU64 = AtomicU64::new(0);
+
+/// Method for listening to new posts from all watched users through Jetstream.
+///
+/// Keeps a single connection open, subscribed to the posts of everyone in the watchlist,
+/// and notifies the watchers whenever one of them posts. Events are replayed from `since`,
+/// and from the last received post on every reconnection, or on every restart (check
+/// `supervisor`), so that no posts are missed.
+/// The subscription is kept in sync with the watchlist every `REFRESH_DELAY` seconds. While no
+/// one is watched, the bot stays disconnected, since Jetstream would send everyone's posts.
+/// Changes to the identities of watched users, e.g. to their handles, are handled as well.
+/// Since those are only reported as they happen, the profiles of everyone in the watchlist are
+/// fetched on start, and those of newly watched users as they're subscribed to.
+/// Accounts of watched users that are deactivated or taken down are suspended, and their posts
+/// ignored, until they're reported active again or found to be so when probed (every
+/// `PROBE_DELAY` seconds). Those that are deleted are unwatched.
+/// Also has a mechanism to handle persistent connection failures, reconnecting in
+/// incrementing intervals and cancelling the job if the error appears to be unrecoverable.
+#[expect(clippy::missing_panics_doc)] // False positive because of unwrap
+pub async fn begin(since: DateTime<Utc>) {
+  let _running = Job::JetstreamListener.running();
+  let url = owned_var_or_else("JETSTREAM_URL", || DEFAULT_JETSTREAM_URL.to_string());
+
+  event!(Level::INFO, "Now listening to Jetstream for new posts.");
+  let watching: Vec<_> = watched_user::get_watching().await.into_iter().collect();
+  tokio::spawn(async move { profile_changed::refresh(&watching).await });
+
+  #[expect(clippy::unwrap_used)] // Current time, always positive
+  let since: u64 = since.timestamp_micros().try_into().unwrap();
+  let mut cursor = since.max(LAST_CURSOR.load(Ordering::Relaxed));
+  let mut failures_in_a_row = 0;
+  loop {
+    let result = listen(&url, &mut cursor, &mut failures_in_a_row).await;
+    if let Err(e) = result {
+      event!(Level::WARN, "(Notice) Lost connection to Jetstream: {e}");
+      if handle_api_failure(&mut failures_in_a_row).await {
+        event!(Level::ERROR, "Stopping listening to Jetstream...");
+        break;
+      }
+    }
+  }
+}
+
+/// Connects to Jetstream, replaying events from `cursor`, and handles all incoming posts,
+/// identity and account changes until the connection fails, or until no one is watched. The
+/// `cursor` is moved forward with every event received.
+///
+/// # Errors
+/// When the connection fails or is closed by the server.
+async fn listen(
+  url: &str,
+  cursor: &mut u64,
+  failures_in_a_row: &mut u64,
+) -> Result<(), jetstream::Error> {
+  let mut watching = wait_for_watched(cursor).await;
+  let mut subscription = Subscription::connect(url, watching.clone(), Some(*cursor)).await?;
+  event!(Level::DEBUG, "Connected to Jetstream at {url}.");
+  *failures_in_a_row = 0;
+
+  let mut refresh = interval(Duration::from_secs(REFRESH_DELAY));
+  let probe_delay = Duration::from_secs(PROBE_DELAY);
+  let mut probe = interval_at(Instant::now() + probe_delay, probe_delay);
+  loop {
+    tokio::select! {
+      update = subscription.next_update() => {
+        match update? {
+          Update::Post(post) => {
+            *cursor = post.time_us;
+            LAST_CURSOR.store(post.time_us, Ordering::Relaxed);
+            on_post(post).await;
+          }
+          Update::IdentityChanged { did, handle, time_us } => {
+            *cursor = time_us;
+            LAST_CURSOR.store(time_us, Ordering::Relaxed);
+            tokio::spawn(on_identity_changed(did, handle));
+          }
+          Update::AccountChanged { did, status, time_us } => {
+            *cursor = time_us;
+            LAST_CURSOR.store(time_us, Ordering::Relaxed);
+            tokio::spawn(on_account_changed(did, status));
+          }
+        }
+      },
+      _ = refresh.tick() => {
+        let current: HashSet<Did> = watched_user::get_watching().await;
+        if current.is_empty() {
+          event!(
+            Level::INFO,
+            "No one is watched anymore. Disconnecting from Jetstream until someone is."
+          );
+          return Ok(());
+        }
+        if current != watching {
+          subscription.update_wanted_dids(current.clone()).await?;
+          let newly_watched: Vec<_> = current.difference(&watching).cloned().collect();
+          tokio::spawn(async move { profile_changed::refresh(&newly_watched).await });
+          watching = current;
+        }
+      }
+      _ = probe.tick() => {
+        tokio::spawn(probe_suspended());
+      }
+    }
+  }
+}
+
+/// Waits until anyone is watched, returning everyone who is. Nothing is missed until then, so
+/// the `cursor` is moved forward to when they're first found, if it had to wait.
+async fn wait_for_watched(cursor: &mut u64) -> HashSet<Did> {
+  let mut refresh = interval(Duration::from_secs(REFRESH_DELAY));
+  let mut waited = false;
+  loop {
+    refresh.tick().await;
+    let watching = watched_user::get_watching().await;
+    if !watching.is_empty() {
+      if waited {
+        #[expect(clippy::unwrap_used)] // Current time, always positive
+        let now: u64 = Utc::now().timestamp_micros().try_into().unwrap();
+        *cursor = now;
+        LAST_CURSOR.store(now, Ordering::Relaxed);
+      }
+      return watching;
+    }
+    if !waited {
+      event!(
+        Level::INFO,
+        "No one is watched. Waiting to connect to Jetstream until someone is."
+      );
+      waited = true;
+    }
+  }
+}
+
+/// Notifies the watchers of the author of a new post or reply.
+async fn on_post(post: PostCreated) {
+  // The subscription might lag behind the watchlist for up to `REFRESH_DELAY` seconds
+  if !watched_user::is_watched(&post.did).await {
+    return;
+  }
+  // Suspended users are left alone until their account is active again
+  if watched_user::is_suspended(&post.did).await {
+    return;
+  }
+  event!(
+    Level::DEBUG,
+    "New post from {} received: {}.",
+    post.did,
+    post.rkey
+  );
+
+  #[expect(clippy::cast_possible_wrap)] // Unix microseconds won't overflow an i64
+  let Some(posted_at) = DateTime::from_timestamp_micros(post.time_us as i64) else {
+    event!(
+      Level::WARN,
+      "(Notice) Received invalid time for post: {}",
+      post.time_us
+    );
+    return;
+  };
+  watched_user::set_last_post_at(&post.did, posted_at).await;
+
+  let feed_post = FeedPost {
+    uri: post.uri(),
+    is_reply: post.is_reply(),
+    cid: post.cid,
+    text: post.record.data.text,
+    indexed_at: posted_at,
+  };
+  tokio::spawn(notify::watcher::many(
+    post.did,
+    None,
+    Notification::Posts(vec![feed_post]),
+  ));
+}
+
+/// Handles the latest profile of a watched user whose identity has changed.
+/// The handle reported by Jetstream is preferred, as the profile might not be up to date yet.
+async fn on_identity_changed(did: Did, handle: Option<String>) {
+  if !watched_user::is_watched(&did).await {
+    return;
+  }
+  event!(Level::DEBUG, "Identity change from {did} received.");
+
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let profile = match get_profile::act(did.parse().unwrap()).await {
+    Ok(profile) => profile_changed::from_view(profile),
+    Err(e) => {
+      event!(
+        Level::WARN,
+        "(Notice) Failed to fetch the profile of {did} after their identity changed: {e}"
+      );
+      return;
+    }
+  };
+  let profile = match handle {
+    Some(handle) => Profile {
+      handle: Some(handle),
+      ..profile
+    },
+    None => profile,
+  };
+  profile_changed::handle(did, profile).await;
+}
+
+/// Handles a change to the status of a watched user's account.
+///
+/// Those that are deactivated or taken down are suspended, since either might be reverted, and
+/// resumed once they're active. Those that are deleted, or suspended for too long (check `user_suspended`), are unwatched.
+pub async fn on_account_changed(did: Did, status: AccountStatus) {
+  if !watched_user::is_watched(&did).await {
+    return;
+  }
+  match status {
+    AccountStatus::Active => {
+      if watched_user::is_suspended(&did).await {
+        resume(&did).await;
+      }
+    }
+    AccountStatus::Unavailable(status) => {
+      event!(
+        Level::INFO,
+        "{did}'s account is unavailable for now ({}).",
+        status.as_deref().unwrap_or("unknown")
+      );
+      if let Err(reason) = user_suspended::handle(&did).await {
+        user_unwatched::handle(did, reason).await;
+      }
+    }
+    AccountStatus::Deleted => {
+      event!(
+        Level::INFO,
+        "{did}'s account was deleted. Will stop watching."
+      );
+      user_unwatched::handle(did, Reason::Gone).await;
+    }
+  }
+}
+
+/// Probes every suspended user, resuming those whose profile can be fetched again.
+/// Those that still can't be reached are unwatched once they've been suspended for too long.
+async fn probe_suspended() {
+  for watched_did in watched_user::get_suspended().await.into_keys() {
+    #[expect(clippy::unwrap_used)] // Did from DB so always valid
+    let res = get_profile::act(watched_did.parse().unwrap()).await;
+    if res.is_ok() {
+      resume(&watched_did).await;
+    } else if let Err(reason) = user_suspended::handle(&watched_did).await {
+      user_unwatched::handle(watched_did, reason).await;
+    }
+  }
+}
+
+/// Lifts the suspension of a user once their account is active again.
+async fn resume(watched_did: &Did) {
+  event!(
+    Level::INFO,
+    "{watched_did}'s account is active again. Resuming watching."
+  );
+  watched_user::resume(watched_did).await;
+}
@@ -1,3 +1,9 @@
+pub mod backfill;
 pub mod command_issuer;
 pub mod command_listener;
+pub mod digest_sender;
+pub mod jetstream_listener;
+pub mod notification_sender;
+pub mod supervisor;
 pub mod user_watcher;
+pub mod watchlist_reloader;
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use repositories::notification_outbox::{self, OutboxNotification};
+use tokio::{task::JoinSet, time::timeout};
+use tracing::{event, Level};
+use utils::api_failure_delay;
+
+use crate::{
+  health::Job,
+  notify::{self, watcher::ENQUEUED},
+};
+
+static DELIVERY_DELAY: u64 = 5; // 5 Seconds
+/// How many notifications are picked up from the outbox at once.
+static BATCH_LIMIT: i64 = 100;
+/// How long notifications picked up from the outbox are kept from other instances. Must be
+/// longer than delivering them can take, or they might be delivered twice.
+static DELIVERY_LEASE: i64 = 15; // 15 Minutes
+/// How long sent notifications are kept in the outbox, so that the same posts aren't notified
+/// to the same watcher again.
+static OUTBOX_RETENTION: i64 = 7; // 7 Days
+/// How many iterations to wait between each pruning of the outbox.
+static PRUNE_EVERY: u32 = 720;
+
+/// Method for delivering the notifications in the outbox (check `notify::watcher`).
+///
+/// Will pick up the due notifications as soon as they're added, or from time to time
+/// (`DELIVERY_DELAY`), and deliver them to each watcher, one watched user at a time.
+/// Notifications that were held for a watcher's digest are delivered together instead.
+/// Failed deliveries are retried in incrementing intervals, just like other API failures,
+/// and are given up on once the maximum retries are reached. Those are left in the outbox
+/// as dead letters, for the hoster to look into.
+/// Notifications are claimed when picked up, so that instances sharing a database
+/// don't deliver the same ones.
+pub async fn begin() {
+  let _running = Job::NotificationSender.running();
+  event!(Level::INFO, "Now delivering notifications.");
+  match notification_outbox::count_dead().await {
+    Ok(0) => {}
+    Ok(dead) => event!(
+      Level::WARN,
+      "(Notice) There are {dead} notifications that failed to be delivered in the outbox."
+    ),
+    Err(e) => event!(Level::WARN, "Failed to count dead notifications: {e}"),
+  }
+
+  let mut iteration = 0;
+  loop {
+    if iteration % PRUNE_EVERY == 0 {
+      prune().await;
+    }
+    iteration = iteration.wrapping_add(1);
+
+    let due = notification_outbox::claim_due(BATCH_LIMIT, TimeDelta::minutes(DELIVERY_LEASE))
+      .await
+      .unwrap_or_else(|e| {
+        event!(Level::WARN, "Failed to get due notifications: {e}");
+        Vec::new()
+      });
+
+    // Batches of notifications to the same watcher about the same watched user, or of those
+    // held for the same watcher's digest, in the order they were first added
+    let mut batches: Vec<Vec<OutboxNotification>> = Vec::new();
+    for notification in due {
+      match batches.iter_mut().find(|batch| {
+        batch[0].watcher_did == notification.watcher_did
+          && batch[0].in_digest == notification.in_digest
+          && (notification.in_digest || batch[0].watched_did == notification.watched_did)
+      }) {
+        Some(batch) => batch.push(notification),
+        None => batches.push(vec![notification]),
+      }
+    }
+
+    let mut set = JoinSet::new();
+    for batch in batches {
+      set.spawn(deliver(batch));
+    }
+    drop(set.join_all().await);
+
+    drop(timeout(Duration::from_secs(DELIVERY_DELAY), ENQUEUED.notified()).await);
+  }
+}
+
+/// Delivers a batch of notifications, scheduling the ones that failed to be retried.
+async fn deliver(batch: Vec<OutboxNotification>) {
+  let OutboxNotification {
+    watcher_did,
+    watched_did,
+    in_digest,
+    ..
+  } = &batch[0];
+
+  let mut sent = Vec::with_capacity(batch.len());
+  let res = if *in_digest {
+    notify::watcher::digest(watcher_did, &batch, &mut sent).await
+  } else {
+    notify::watcher::deliver(watcher_did, watched_did, &batch, &mut sent).await
+  };
+  notification_outbox::mark_sent(&sent).await;
+  metrics::NOTIFICATIONS
+    .with_label_values(&["sent"])
+    .inc_by(sent.len() as u64);
+  let Err(e) = res else {
+    return;
+  };
+
+  let error = e.to_string();
+  for notification in batch.iter().filter(|n| !sent.contains(&n.id)) {
+    let Err(e) = retry_or_give_up(notification, &error).await else {
+      continue;
+    };
+    event!(
+      Level::WARN,
+      "Failed to save a failed delivery of notification {}: {e}",
+      notification.id
+    );
+  }
+  if *in_digest {
+    event!(
+      Level::WARN,
+      "(Notice) Failed to send digest to {watcher_did}. Will retry later: {e}"
+    );
+  } else {
+    event!(
+      Level::WARN,
+      "(Notice) Failed to notify {watcher_did} about {watched_did}. Will retry later: {e}"
+    );
+  }
+}
+
+/// Schedules a notification that failed to be delivered to be retried, unless it has
+/// already been retried too many times, in which case it's given up on.
+///
+/// # Errors
+/// Propagates any errors that occur during the process of contacting the database.
+async fn retry_or_give_up(notification: &OutboxNotification, error: &str) -> sqlx::Result<()> {
+  #[expect(clippy::cast_sign_loss)] // Never negative
+  let Some(delay) = api_failure_delay(notification.attempts as u64) else {
+    event!(
+      Level::ERROR,
+      "(Notice) Gave up on notifying {} about {} after {} attempts. Left in the outbox as notification {}. Error: {error}",
+      notification.watcher_did,
+      notification.watched_did,
+      notification.attempts + 1,
+      notification.id
+    );
+    metrics::NOTIFICATIONS.with_label_values(&["dead"]).inc();
+    return notification_outbox::dead_letter(notification.id, error).await;
+  };
+
+  metrics::NOTIFICATIONS.with_label_values(&["failed"]).inc();
+  #[expect(clippy::unwrap_used)] // Never more than a minute
+  let next_attempt_at = Utc::now() + TimeDelta::from_std(delay).unwrap();
+  notification_outbox::retry_at(notification.id, next_attempt_at, error).await
+}
+
+/// Removes the notifications that were sent long ago from the outbox.
+async fn prune() {
+  match notification_outbox::prune(Utc::now() - TimeDelta::days(OUTBOX_RETENTION)).await {
+    Ok(0) => {}
+    Ok(pruned) => event!(
+      Level::DEBUG,
+      "Pruned {pruned} old notifications from the outbox."
+    ),
+    Err(e) => event!(Level::WARN, "Failed to prune the notification outbox: {e}"),
+  }
+}
@@ -0,0 +1,191 @@
+use std::{collections::VecDeque, future::Future, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use environment::owned_var_or;
+use lazy_static::lazy_static;
+use tokio::time::sleep;
+use tracing::{event, Level};
+use utils::backoff;
+
+use crate::health::Job;
+
+lazy_static! {
+  static ref MAX_RESTARTS: usize = owned_var_or("JOB_MAX_RESTARTS", 10);
+}
+
+/// The window in which at most `MAX_RESTARTS` restarts of a job are allowed.
+static RESTART_WINDOW: i64 = 60 * 60; // 1 Hour
+/// How long to wait before the first restart, doubled with each restart in a row.
+static BACKOFF_MIN: u64 = 1; // 1 Second
+static BACKOFF_MAX: u64 = 5 * 60; // 5 Minutes
+/// How long a job must have run for before it's considered to have recovered, so that the
+/// backoff starts over.
+static RECOVERED_AFTER: i64 = 10 * 60; // 10 Minutes
+
+/// What to do with a job that has stopped.
+#[derive(Debug, PartialEq, Eq)]
+enum Restart {
+  /// Restart it after waiting for a while.
+  After(Duration),
+  /// It was restarted too many times within `RESTART_WINDOW`, so give up on it.
+  GiveUp { restarts: usize },
+}
+
+/// The restarts of a job so far.
+#[derive(Debug, Default)]
+struct Restarts {
+  /// When it was restarted within the last `RESTART_WINDOW`, from oldest to newest.
+  at: VecDeque<DateTime<Utc>>,
+  /// How many times it was restarted without recovering in between.
+  in_a_row: u32,
+}
+impl Restarts {
+  /// Decides what to do with a job that ran from `started_at` until `stopped_at`, allowing at
+  /// most `max_restarts` restarts within `RESTART_WINDOW`. Restarts are recorded as happening
+  /// once their backoff is over.
+  fn next(
+    &mut self,
+    started_at: DateTime<Utc>,
+    stopped_at: DateTime<Utc>,
+    max_restarts: usize,
+  ) -> Restart {
+    if stopped_at - started_at >= TimeDelta::seconds(RECOVERED_AFTER) {
+      self.in_a_row = 0;
+    }
+    while self
+      .at
+      .front()
+      .is_some_and(|at| stopped_at - *at >= TimeDelta::seconds(RESTART_WINDOW))
+    {
+      self.at.pop_front();
+    }
+    if self.at.len() >= max_restarts {
+      return Restart::GiveUp {
+        restarts: self.at.len(),
+      };
+    }
+
+    let delay = backoff(
+      Duration::from_secs(BACKOFF_MIN),
+      Duration::from_secs(BACKOFF_MAX),
+      self.in_a_row,
+    );
+    #[expect(clippy::unwrap_used)] // Never more than `BACKOFF_MAX`
+    self
+      .at
+      .push_back(stopped_at + TimeDelta::from_std(delay).unwrap());
+    self.in_a_row += 1;
+    Restart::After(delay)
+  }
+}
+
+/// Method for running a job that's meant to run until the bot exits, restarting it whenever
+/// it stops, be it because it gave up or because it panicked.
+///
+/// Restarts are delayed exponentially, from `BACKOFF_MIN` up to `BACKOFF_MAX`, starting over
+/// once the job runs for long enough (`RECOVERED_AFTER`). If the job has to be restarted more
+/// than `JOB_MAX_RESTARTS` times within `RESTART_WINDOW`, it's deemed unrecoverable and the
+/// supervisor gives up, returning so that the bot can exit.
+pub async fn supervise<F, Fut>(job: Job, mut start: F)
+where
+  F: FnMut() -> Fut + Send,
+  Fut: Future<Output = ()> + Send + 'static,
+{
+  let mut restarts = Restarts::default();
+  loop {
+    let started_at = Utc::now();
+    match tokio::spawn(start()).await {
+      Ok(()) => event!(Level::ERROR, "The {} job has stopped.", job.name()),
+      Err(e) => event!(Level::ERROR, "The {} job has crashed: {e}", job.name()),
+    }
+
+    match restarts.next(started_at, Utc::now(), *MAX_RESTARTS) {
+      Restart::GiveUp { restarts } => {
+        event!(
+          Level::ERROR,
+          "The {} job was restarted {restarts} times within the last hour. Giving up...",
+          job.name()
+        );
+        return;
+      }
+      Restart::After(delay) => {
+        event!(
+          Level::WARN,
+          "Restarting the {} job in {} seconds...",
+          job.name(),
+          delay.as_secs()
+        );
+        sleep(delay).await;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use chrono::{DateTime, TimeDelta, Utc};
+
+  use super::{Restart, Restarts, RECOVERED_AFTER, RESTART_WINDOW};
+
+  fn at(seconds: i64) -> DateTime<Utc> {
+    DateTime::UNIX_EPOCH + TimeDelta::seconds(seconds)
+  }
+
+  /// Stops the job right after it was last restarted, or at `from` if it never was.
+  fn crash(restarts: &mut Restarts, from: i64, max_restarts: usize) -> Restart {
+    let started_at = restarts.at.back().copied().unwrap_or_else(|| at(from));
+    restarts.next(started_at, started_at, max_restarts)
+  }
+
+  #[test]
+  fn backoff_doubles_up_to_max() {
+    let mut restarts = Restarts::default();
+    let delays: Vec<_> = (0..11)
+      .map(|_| match crash(&mut restarts, 0, 100) {
+        Restart::After(delay) => delay.as_secs(),
+        Restart::GiveUp { .. } => panic!("Gave up too early"),
+      })
+      .collect();
+    assert_eq!(delays, [1, 2, 4, 8, 16, 32, 64, 128, 256, 300, 300]);
+  }
+
+  #[test]
+  fn backoff_starts_over_once_recovered() {
+    let mut restarts = Restarts::default();
+    for _ in 0..4 {
+      crash(&mut restarts, 0, 100);
+    }
+    let started_at = *restarts.at.back().expect("Was restarted");
+    let stopped_at = started_at + TimeDelta::seconds(RECOVERED_AFTER);
+    assert_eq!(
+      restarts.next(started_at, stopped_at, 100),
+      Restart::After(Duration::from_secs(1))
+    );
+  }
+
+  #[test]
+  fn gives_up_after_max_restarts_within_window() {
+    let mut restarts = Restarts::default();
+    for _ in 0..3 {
+      assert!(matches!(crash(&mut restarts, 0, 3), Restart::After(_)));
+    }
+    assert_eq!(crash(&mut restarts, 0, 3), Restart::GiveUp { restarts: 3 });
+  }
+
+  #[test]
+  fn forgets_restarts_outside_window() {
+    let mut restarts = Restarts::default();
+    for _ in 0..3 {
+      crash(&mut restarts, 0, 3);
+    }
+    let started_at =
+      *restarts.at.front().expect("Was restarted") + TimeDelta::seconds(RESTART_WINDOW);
+    assert!(matches!(
+      restarts.next(started_at, started_at, 3),
+      Restart::After(_)
+    ));
+    assert_eq!(restarts.at.len(), 3);
+  }
+}
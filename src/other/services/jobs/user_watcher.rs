@@ -1,97 +1,748 @@
-use std::{cmp, time::Duration};
+use std::{
+  cmp::Reverse,
+  collections::{BinaryHeap, HashMap},
+  hash::{BuildHasher, RandomState},
+  time::Duration,
+};
 
 use atrium_api::types::string::AtIdentifier;
-use chrono::{DateTime, Utc};
-use repositories::watched_user;
+use chrono::{DateTime, TimeDelta, Utc};
+use environment::owned_var_or;
+use lazy_static::lazy_static;
+use repositories::{
+  post_activity,
+  watched_user::{self, Profile},
+};
 
-use tokio::time::sleep;
+use tokio::{
+  sync::{Mutex, Notify},
+  time::sleep,
+};
 use tracing::{event, Level};
 
-use bsky::get_last_post_time;
-use utils::{handle_api_failure, Did};
+use bsky::{get_posts_since, get_profiles, FeedPost};
+use utils::{backoff, handle_api_failure, Did};
 
-use crate::{notify, user_unwatched};
+use crate::{
+  health::Job,
+  notify::{self, watcher::Notification},
+  profile_changed, user_suspended,
+  user_unwatched::{self, Reason},
+};
 
-/// Method for initializing the watching of all users found in the database.
-pub async fn begin() {
-  let watching = watched_user::get_watching().await;
+lazy_static! {
+  static ref SCHEDULER: Mutex<Scheduler> = Mutex::default();
+  /// Wakes the scheduler up whenever a poll is scheduled, in case it's due before the next one.
+  static ref SCHEDULED: Notify = Notify::new();
+  static ref POLL_INTERVAL_MIN: TimeDelta =
+    TimeDelta::seconds(owned_var_or("POLL_INTERVAL_MIN", 15));
+  static ref POLL_INTERVAL_MAX: TimeDelta =
+    TimeDelta::seconds(owned_var_or("POLL_INTERVAL_MAX", 600)).max(*POLL_INTERVAL_MIN);
+}
 
-  event!(Level::INFO, "Now watching all users' posts.");
+/// How many times a user is polled in the time they usually go between posts.
+static POLLS_PER_GAP: i32 = 10;
+/// The longest wait before re-probing a suspended user.
+static REARM_DELAY_MAX: u64 = 12 * 60 * 60; // 12 Hours
+
+/// What the scheduler knows about the polling of a watched user, for debugging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollStatus {
+  /// When the user is polled next. `None` while a poll is in flight.
+  pub next_poll_at: Option<DateTime<Utc>>,
+  /// How long it waits between polls, as of the last time it was scheduled.
+  pub interval: TimeDelta,
+  pub last_success: Option<DateTime<Utc>>,
+  pub failures_in_a_row: u64,
+  /// How many times in a row polling was given up on, and then re-armed.
+  pub rearms_in_a_row: u32,
+  /// Since when the user has been suspended, if they are (see `suspend`).
+  pub suspended_since: Option<DateTime<Utc>>,
+}
 
-  for watched_did in watching {
-    tokio::spawn(new(watched_did));
+struct Poll {
+  /// Posts indexed up until then were already handled.
+  since: DateTime<Utc>,
+  /// The user's post count as of the last time their feed was fetched.
+  posts_count: Option<i64>,
+  /// The user's profile as of the last `check`. Changes to it are handled by `profile_changed`.
+  profile: Option<Profile>,
+  /// Whether the feed should be fetched once more even if the post count didn't change, given
+  /// that the count may be updated before the new post makes it into the feed.
+  recheck: bool,
+  /// When the user made their latest posts, from newest to oldest. Loaded along with the feed.
+  activity: Vec<DateTime<Utc>>,
+  status: PollStatus,
+}
+impl Poll {
+  /// Picks how long to wait between polls: a fraction of how long the user usually goes between
+  /// posts, or of how long it's been since they last posted if that's shorter. So users who post
+  /// often, or who were recently active, are polled more often. So are those with more watchers.
+  fn interval(&self, now: DateTime<Utc>, watchers: usize) -> TimeDelta {
+    let since_last_post = now - self.activity.first().copied().unwrap_or(self.since);
+    let mut gaps: Vec<_> = self.activity.windows(2).map(|w| w[0] - w[1]).collect();
+    gaps.sort_unstable();
+    let usual_gap = gaps.get(gaps.len() / 2).copied().unwrap_or(since_last_post);
+
+    #[expect(clippy::unwrap_used)] // At most 64
+    let popularity = i32::try_from(watchers.max(1).ilog2() + 1).unwrap();
+    let interval = usual_gap.min(since_last_post) / POLLS_PER_GAP / popularity;
+    interval.clamp(*POLL_INTERVAL_MIN, *POLL_INTERVAL_MAX)
   }
 }
 
-static WATCH_DELAY: i64 = 15; // 15 Seconds
-/// Method for watching a user's posts.
-/// Will fetch the last post time of the user from time to time (`WATCH_DELAY`),
-/// and then notify the watchers if a new post is found.
-/// Has a basic compensation mechanism that tries to, on average and as much as possible,
-/// wait for exactly `WATCH_DELAY` seconds between each loop.
-/// Also has a mechanism to handle persistent API failures, cancelling the job if the
-/// error appears to be unrecoverable, logging the error.
-#[expect(clippy::missing_panics_doc)] // False positive because of unwrap
-pub async fn new(watched_did: Did) {
-  let watched_did_as_at = watched_did.parse::<AtIdentifier>().unwrap();
-  let mut failures_in_a_row = 0;
-  let mut last_notified_watchers: DateTime<Utc> = Utc::now();
-  loop {
-    if !watched_user::is_watched(&watched_did).await {
-      event!(
-        Level::INFO,
-        "User {watched_did} is no longer being watched."
-      );
-      break;
-    }
-
-    let before_task = Utc::now();
-    match get_last_post_time::act(watched_did_as_at.clone()).await {
-      Err(bsky::Error::Api) => {
-        event!(
-          Level::WARN,
-          "(Notice) Error fetching last post time for {watched_did}."
-        );
-        if handle_api_failure(&mut failures_in_a_row).await {
-          tokio::spawn(user_unwatched::handle(watched_did.clone(), false));
-          break;
+/// The queue of watched users to be polled, by when they're due.
+/// Entries made stale by rescheduling are skipped when popped, so that each user is only ever
+/// due once, and never while their poll is still in flight.
+#[derive(Default)]
+struct Scheduler {
+  queue: BinaryHeap<Reverse<(DateTime<Utc>, Did)>>,
+  polls: HashMap<Did, Poll>,
+}
+impl Scheduler {
+  /// Adds a user, unless they're already being polled, in which case posts are only fetched
+  /// from `since` onwards if that's later than before.
+  fn add(&mut self, watched_did: Did, since: DateTime<Utc>, at: DateTime<Utc>) {
+    if let Some(poll) = self.polls.get_mut(&watched_did) {
+      poll.since = poll.since.max(since);
+      return;
+    }
+    self.polls.insert(
+      watched_did.clone(),
+      Poll {
+        since,
+        posts_count: None,
+        profile: None,
+        recheck: false,
+        activity: Vec::new(),
+        status: PollStatus {
+          next_poll_at: Some(at),
+          interval: *POLL_INTERVAL_MIN,
+          last_success: None,
+          failures_in_a_row: 0,
+          rearms_in_a_row: 0,
+          suspended_since: None,
+        },
+      },
+    );
+    self.queue.push(Reverse((at, watched_did)));
+  }
+
+  /// Schedules the next poll of a user after their interval, give or take some jitter.
+  fn schedule(&mut self, watched_did: &Did, now: DateTime<Utc>, watchers: usize) {
+    if let Some(poll) = self.polls.get_mut(watched_did) {
+      let interval = poll.interval(now, watchers);
+      let at = now + interval + jitter(interval / 5);
+      poll.status.interval = interval;
+      self.schedule_at(watched_did, at);
+    }
+  }
+
+  fn schedule_at(&mut self, watched_did: &Did, at: DateTime<Utc>) {
+    if let Some(poll) = self.polls.get_mut(watched_did) {
+      poll.status.next_poll_at = Some(at);
+      self.queue.push(Reverse((at, watched_did.clone())));
+    }
+  }
+
+  /// Takes all the users that are due, marking their polls as in flight. Users due within
+  /// `window` after them are taken early to fill up the last batch of `batch_size`, so that
+  /// users due around the same time are checked together.
+  fn take_due(&mut self, now: DateTime<Utc>, window: TimeDelta, batch_size: usize) -> Vec<Did> {
+    let mut due = Vec::new();
+    while let Some(Reverse((at, _))) = self.queue.peek() {
+      let is_early = *at > now;
+      if *at > now + window || (is_early && due.len() % batch_size == 0) {
+        break;
+      }
+      #[expect(clippy::unwrap_used)] // Peeked above
+      let Reverse((at, watched_did)) = self.queue.pop().unwrap();
+      if let Some(poll) = self.polls.get_mut(&watched_did) {
+        if poll.status.next_poll_at == Some(at) {
+          poll.status.next_poll_at = None;
+          let lag = (now - at).to_std().unwrap_or_default();
+          metrics::POLL_LAG
+            .with_label_values(&[&watched_did])
+            .set(lag.as_secs_f64());
+          due.push(watched_did);
         }
-        continue;
       }
-      Err(bsky::Error::BskyBug) => {
-        event!(Level::ERROR, "Stopping watching {watched_did}...");
-        tokio::spawn(user_unwatched::handle(watched_did.clone(), false));
-        break;
+    }
+    due
+  }
+
+  fn next_due(&self) -> Option<DateTime<Utc>> {
+    self.queue.peek().map(|Reverse((at, _))| *at)
+  }
+}
+
+/// Method for polling all users found in the database, and any that are watched later on.
+/// Posts made up until `since` are expected to have been handled already (see `backfill`).
+///
+/// Each user is polled every so often depending on how active they are (see `Poll::interval`),
+/// give or take some jitter so that polls don't all happen at once, and with at most one poll in
+/// flight per user. Users stop being polled once they're no longer watched.
+///
+/// Polling is done in batches: the profiles of the users that are due are fetched together,
+/// and only the feeds of those whose post count changed are fetched (see `check`).
+pub async fn begin(since: DateTime<Utc>) {
+  let _running = Job::UserWatcher.running();
+  let watching = watched_user::get_watching().await;
+  let suspended = watched_user::get_suspended().await;
+  {
+    let mut scheduler = SCHEDULER.lock().await;
+    let now = Utc::now();
+    for watched_did in watching {
+      // Spread out over the first interval
+      scheduler.add(watched_did.clone(), since, now + jitter(*POLL_INTERVAL_MIN));
+      if let Some(poll) = scheduler.polls.get_mut(&watched_did) {
+        poll.status.suspended_since = poll
+          .status
+          .suspended_since
+          .or_else(|| suspended.get(&watched_did).copied());
       }
-      Err(bsky::Error::Other(get_last_post_time::Error::UserOptedOut)) => {
-        event!(
-          Level::INFO,
-          "{watched_did} has opted out of the watchlist. Will stop watching."
-        );
-        tokio::spawn(user_unwatched::handle(watched_did.clone(), true));
-        break;
+    }
+  }
+
+  event!(Level::INFO, "Now watching all users' posts.");
+
+  loop {
+    let next_due = {
+      let mut scheduler = SCHEDULER.lock().await;
+      let due = scheduler.take_due(Utc::now(), *POLL_INTERVAL_MIN / 2, get_profiles::MAX_ACTORS);
+      for batch in due.chunks(get_profiles::MAX_ACTORS) {
+        tokio::spawn(check(batch.to_vec()));
       }
-      Err(bsky::Error::Other(get_last_post_time::Error::ZeroPosts)) => {
-        event!(Level::DEBUG, "API returned zero posts for {watched_did}.");
+      scheduler.next_due()
+    };
+
+    let wait = next_due.map_or(*POLL_INTERVAL_MAX, |at| at - Utc::now());
+    tokio::select! {
+      () = sleep(wait.to_std().unwrap_or(Duration::ZERO)) => {},
+      () = SCHEDULED.notified() => {},
+    }
+  }
+}
+
+/// Method for starting to poll a newly watched user, from `since` onwards.
+/// Does nothing if they're already being polled.
+pub async fn watch(watched_did: Did, since: DateTime<Utc>) {
+  SCHEDULER.lock().await.add(watched_did, since, Utc::now());
+  SCHEDULED.notify_one();
+}
+
+/// Gets the status of the polling of every user, for debugging. Served at `/debug/polls`.
+pub async fn inspect() -> HashMap<Did, PollStatus> {
+  SCHEDULER
+    .lock()
+    .await
+    .polls
+    .iter()
+    .map(|(watched_did, poll)| (watched_did.clone(), poll.status.clone()))
+    .collect()
+}
+
+/// Method for checking which users of a batch have posted since they were last polled, by
+/// comparing their post counts, and then polling only those. The rest are scheduled again.
+/// Users whose count is unknown, e.g. because their profile is unavailable, are always polled.
+/// Note that a new post can't be told apart from a deleted one this way, so posts made
+/// along with a deletion within the same interval are missed.
+/// Changes to their profiles, e.g. to their handles, are handled along the way.
+/// Users that are no longer watched stop being polled instead.
+async fn check(watched_dids: Vec<Did>) {
+  let mut still_watched = Vec::with_capacity(watched_dids.len());
+  for watched_did in watched_dids {
+    if watched_user::is_watched(&watched_did).await {
+      still_watched.push(watched_did);
+    } else {
+      stop_polling(&watched_did).await;
+    }
+  }
+  let watched_dids = still_watched;
+  if watched_dids.is_empty() {
+    return;
+  }
+
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let actors = watched_dids
+    .iter()
+    .map(|did| did.parse().unwrap())
+    .collect();
+  let profiles = match get_profiles::act(actors).await {
+    Ok(profiles) => profiles,
+    Err(e) => {
+      event!(
+        Level::WARN,
+        "(Notice) Error fetching profiles, polling their feeds instead: {e}"
+      );
+      for watched_did in watched_dids {
+        tokio::spawn(poll(watched_did, None, false));
       }
-      Ok(output) => {
-        if output > last_notified_watchers {
-          last_notified_watchers = output;
-          tokio::spawn(notify::watcher::many(watched_did.clone(), None, true));
+      return;
+    }
+  };
+  let profiles: HashMap<String, (Option<i64>, Profile)> = profiles
+    .into_iter()
+    .map(|profile| {
+      let posts_count = profile.posts_count;
+      (
+        String::from(profile.did.clone()),
+        (posts_count, profile_changed::from_view(profile)),
+      )
+    })
+    .collect();
+
+  let mut watchers = HashMap::new();
+  for watched_did in &watched_dids {
+    watchers.insert(watched_did.clone(), count_watchers(watched_did).await);
+  }
+
+  let now = Utc::now();
+  let mut changed = Vec::new();
+  let mut changed_profiles = Vec::new();
+  let mut scheduler = SCHEDULER.lock().await;
+  for watched_did in watched_dids {
+    let Some(poll) = scheduler.polls.get_mut(&watched_did) else {
+      continue;
+    };
+    let (posts_count, profile) = profiles
+      .get(&*watched_did)
+      .map_or((None, None), |(posts_count, profile)| {
+        (*posts_count, Some(profile))
+      });
+    if let Some(profile) = profile.filter(|profile| poll.profile.as_ref() != Some(profile)) {
+      poll.profile = Some(profile.clone());
+      changed_profiles.push((watched_did.clone(), profile.clone()));
+    }
+    let count_changed = posts_count.is_none() || posts_count != poll.posts_count;
+    // Suspended users are only resumed once their feed is fetched
+    if count_changed || poll.recheck || poll.status.suspended_since.is_some() {
+      changed.push((watched_did, posts_count, count_changed));
+    } else {
+      poll.status.last_success = Some(now);
+      poll.status.failures_in_a_row = 0;
+      poll.status.rearms_in_a_row = 0;
+      scheduler.schedule(&watched_did, now, watchers[&watched_did]);
+    }
+  }
+  drop(scheduler);
+
+  for (watched_did, profile) in changed_profiles {
+    tokio::spawn(profile_changed::handle(watched_did, profile));
+  }
+  for (watched_did, posts_count, recheck) in changed {
+    tokio::spawn(poll(watched_did, posts_count, recheck));
+  }
+}
+
+/// Method for polling a user's posts once.
+/// Will fetch all the posts of the user newer than the last one seen, and then notify the
+/// watchers about them, scheduling the next poll afterwards. The post count it was polled for,
+/// and whether to `recheck`, are kept for the next `check`.
+/// Also has a mechanism to handle persistent API failures, which are most likely outages, so
+/// instead of no longer polling the user, they're suspended until they can be reached again
+/// (see `suspend`).
+async fn poll(watched_did: Did, posts_count: Option<i64>, recheck: bool) {
+  let Some((since, mut failures_in_a_row)) = SCHEDULER
+    .lock()
+    .await
+    .polls
+    .get(&watched_did)
+    .map(|poll| (poll.since, poll.status.failures_in_a_row))
+  else {
+    return;
+  };
+
+  if !watched_user::is_watched(&watched_did).await {
+    stop_polling(&watched_did).await;
+    return;
+  }
+
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let watched_did_as_at = watched_did.parse::<AtIdentifier>().unwrap();
+  // Whether the user opted out, if they should no longer be watched
+  let stop_watching = match get_posts_since::act(watched_did_as_at, since, None).await {
+    Err(bsky::Error::Api) => {
+      event!(
+        Level::WARN,
+        "(Notice) Error fetching new posts for {watched_did}."
+      );
+      // Backs off while the poll is still in flight, then retries right away
+      let gave_up = handle_api_failure(&mut failures_in_a_row).await;
+      if gave_up {
+        suspend(&watched_did).await
+      } else {
+        let mut scheduler = SCHEDULER.lock().await;
+        if let Some(poll) = scheduler.polls.get_mut(&watched_did) {
+          poll.status.failures_in_a_row = failures_in_a_row;
         }
+        scheduler.schedule_at(&watched_did, Utc::now());
+        drop(scheduler);
+        SCHEDULED.notify_one();
+        return;
       }
     }
-    failures_in_a_row = 0;
-    let after_task = Utc::now();
+    Err(bsky::Error::BskyBug) => {
+      event!(Level::ERROR, "Failed to poll {watched_did}.");
+      suspend(&watched_did).await
+    }
+    Err(bsky::Error::Other(get_posts_since::Error::UserOptedOut)) => {
+      event!(
+        Level::INFO,
+        "{watched_did} has opted out of the watchlist. Will stop watching."
+      );
+      Some(Reason::OptedOut)
+    }
+    Err(bsky::Error::Other(
+      get_posts_since::Error::AccountDeactivated | get_posts_since::Error::AccountTakedown,
+    )) => {
+      // Either might be reverted, so they're retried later on
+      event!(
+        Level::INFO,
+        "{watched_did}'s account is unavailable for now."
+      );
+      suspend(&watched_did).await
+    }
+    Err(bsky::Error::Other(get_posts_since::Error::AccountNotFound)) => {
+      event!(
+        Level::INFO,
+        "{watched_did}'s account no longer exists. Will stop watching."
+      );
+      Some(Reason::Gone)
+    }
+    Ok(posts) => {
+      on_polled(&watched_did, posts, posts_count, recheck).await;
+      None
+    }
+  };
+
+  if let Some(reason) = stop_watching {
+    SCHEDULER.lock().await.polls.remove(&watched_did);
+    drop(metrics::POLL_LAG.remove_label_values(&[&watched_did]));
+    tokio::spawn(user_unwatched::handle(watched_did, reason));
+  }
+}
+
+/// Stops polling a user that is no longer being watched.
+async fn stop_polling(watched_did: &Did) {
+  event!(
+    Level::INFO,
+    "User {watched_did} is no longer being watched."
+  );
+  SCHEDULER.lock().await.polls.remove(watched_did);
+  drop(metrics::POLL_LAG.remove_label_values(&[watched_did]));
+}
+
+/// Notifies the watchers of a user about the posts found by a successful poll, and schedules
+/// the next one, resuming the user if they were suspended.
+async fn on_polled(
+  watched_did: &Did,
+  posts: Vec<FeedPost>,
+  posts_count: Option<i64>,
+  recheck: bool,
+) {
+  let last_post_at = posts.last().map(|post| post.indexed_at);
+  if let Some(last_post_at) = last_post_at {
+    watched_user::set_last_post_at(watched_did, last_post_at).await;
+    let activity: Vec<_> = posts
+      .iter()
+      .map(|post| (post.uri.clone(), post.indexed_at))
+      .collect();
+    post_activity::record(watched_did, &activity).await;
+    tokio::spawn(notify::watcher::many(
+      watched_did.clone(),
+      None,
+      Notification::Posts(posts),
+    ));
+  }
+
+  let activity = post_activity::get_recent(watched_did).await;
+  let watchers = count_watchers(watched_did).await;
+  let now = Utc::now();
+  let mut scheduler = SCHEDULER.lock().await;
+  let mut was_suspended = false;
+  if let Some(poll) = scheduler.polls.get_mut(watched_did) {
+    was_suspended = poll.status.suspended_since.take().is_some();
+    poll.activity = activity;
+    if let Some(last_post_at) = last_post_at {
+      poll.since = poll.since.max(last_post_at);
+    }
+    poll.posts_count = posts_count;
+    poll.recheck = recheck;
+    poll.status.last_success = Some(now);
+    poll.status.failures_in_a_row = 0;
+    poll.status.rearms_in_a_row = 0;
+  }
+  scheduler.schedule(watched_did, now, watchers);
+  drop(scheduler);
+  if was_suspended {
+    resume(watched_did).await;
+  }
+}
+
+/// Suspends a user that can't be polled for now, e.g. because of an outage or because their
+/// account is deactivated or taken down, so that they're kept watched.
+///
+/// They're only re-probed every so often (see `rearm_delay`) until they can be reached again.
+/// The suspension is saved, so it's kept across restarts (check `user_suspended`).
+///
+/// # Returns
+/// `Some(Reason::Gone)` if the user has been suspended for longer than `MAX_SUSPENSION_DAYS`,
+/// in which case they should no longer be watched.
+async fn suspend(watched_did: &Did) -> Option<Reason> {
+  let suspended_since = match user_suspended::handle(watched_did).await {
+    Ok(suspended_since) => suspended_since,
+    Err(reason) => return Some(reason),
+  };
+
+  let now = Utc::now();
+  let mut scheduler = SCHEDULER.lock().await;
+  let poll = scheduler.polls.get_mut(watched_did)?;
+  let delay = rearm_delay(poll.status.rearms_in_a_row);
+  poll.status.failures_in_a_row = 0;
+  poll.status.rearms_in_a_row += 1;
+  poll.status.suspended_since = Some(suspended_since);
+  scheduler.schedule_at(watched_did, now + delay);
+  drop(scheduler);
+  SCHEDULED.notify_one();
+  event!(
+    Level::WARN,
+    "(Notice) Suspended watching {watched_did}. Trying again in {} minutes.",
+    delay.num_minutes()
+  );
+  None
+}
+
+/// Lifts the suspension of a user once they can be reached again.
+async fn resume(watched_did: &Did) {
+  event!(
+    Level::INFO,
+    "{watched_did} can be reached again. Resuming watching."
+  );
+  watched_user::resume(watched_did).await;
+}
+
+/// How long to wait before re-probing a suspended user after giving up on them `rearms_in_a_row`
+/// times: `POLL_INTERVAL_MAX`, doubled with each time, up to `REARM_DELAY_MAX`.
+fn rearm_delay(rearms_in_a_row: u32) -> TimeDelta {
+  #[expect(clippy::unwrap_used)] // Never negative
+  let min = POLL_INTERVAL_MAX.to_std().unwrap();
+  let max = Duration::from_secs(REARM_DELAY_MAX).max(min);
+  let delay = backoff(min, max, rearms_in_a_row);
+  #[expect(clippy::unwrap_used)] // Never more than `REARM_DELAY_MAX`
+  TimeDelta::from_std(delay).unwrap()
+}
+
+async fn count_watchers(watched_did: &Did) -> usize {
+  watched_user::get_watchers(watched_did)
+    .await
+    .map_or(0, |watchers| watchers.len())
+}
+
+/// A random delay of up to `max`.
+fn jitter(max: TimeDelta) -> TimeDelta {
+  let max = u64::try_from(max.num_milliseconds())
+    .unwrap_or_default()
+    .max(1);
+  let random = RandomState::new().hash_one(Utc::now()) % max;
+  TimeDelta::milliseconds(i64::try_from(random).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use bsky::get_profiles::MAX_ACTORS;
+  use chrono::{DateTime, TimeDelta, Utc};
+  use utils::Did;
+
+  use super::{
+    rearm_delay, Poll, PollStatus, Scheduler, POLL_INTERVAL_MAX, POLL_INTERVAL_MIN, REARM_DELAY_MAX,
+  };
 
-    let task_delta = after_task
-      .signed_duration_since(before_task)
-      .num_milliseconds();
+  fn did(i: usize) -> Did {
+    Arc::from(format!("did:plc:user{i}"))
+  }
+
+  /// A poll of a user watched since `since`, who posted at `activity`, from newest to oldest.
+  fn poll(since: DateTime<Utc>, activity: Vec<DateTime<Utc>>) -> Poll {
+    Poll {
+      since,
+      posts_count: None,
+      profile: None,
+      recheck: false,
+      activity,
+      status: PollStatus {
+        next_poll_at: None,
+        interval: *POLL_INTERVAL_MIN,
+        last_success: None,
+        failures_in_a_row: 0,
+        rearms_in_a_row: 0,
+        suspended_since: None,
+      },
+    }
+  }
+
+  /// Posts made `ago` before `now`, from newest to oldest.
+  fn posts(now: DateTime<Utc>, ago: &[TimeDelta]) -> Vec<DateTime<Utc>> {
+    ago.iter().map(|ago| now - *ago).collect()
+  }
+
+  #[test]
+  fn interval_without_history_follows_time_since_watched() {
+    let now = Utc::now();
+    let poll = poll(now - TimeDelta::hours(1), Vec::new());
+    assert_eq!(poll.interval(now, 1), TimeDelta::minutes(6));
+  }
+
+  #[test]
+  fn interval_follows_the_usual_gap_between_posts() {
+    let now = Utc::now();
+    let activity = posts(
+      now,
+      &[
+        TimeDelta::hours(1),
+        TimeDelta::hours(2),
+        TimeDelta::hours(3),
+        TimeDelta::hours(5),
+      ],
+    );
+    let poll = poll(now - TimeDelta::days(30), activity);
+    assert_eq!(poll.interval(now, 1), TimeDelta::minutes(6));
+  }
+
+  #[test]
+  fn interval_shrinks_after_a_burst_of_activity() {
+    let now = Utc::now();
+    // Usually posts once a day, but just posted twice in a row
+    let mut activity = posts(now, &[TimeDelta::minutes(10), TimeDelta::minutes(30)]);
+    activity.extend(posts(
+      now,
+      &[TimeDelta::days(1), TimeDelta::days(2), TimeDelta::days(3)],
+    ));
+    let poll = poll(now - TimeDelta::days(30), activity);
+    assert_eq!(poll.interval(now, 1), TimeDelta::minutes(1));
+  }
+
+  #[test]
+  fn interval_shrinks_with_more_watchers() {
+    let now = Utc::now();
+    let poll = poll(now - TimeDelta::hours(1), Vec::new());
+    let cases = [
+      (0, TimeDelta::minutes(6)),
+      (1, TimeDelta::minutes(6)),
+      (2, TimeDelta::minutes(3)),
+      (3, TimeDelta::minutes(3)),
+      (4, TimeDelta::minutes(2)),
+      (8, TimeDelta::seconds(90)),
+      (1000, TimeDelta::seconds(36)),
+    ];
+    for (watchers, expected) in cases {
+      assert_eq!(
+        poll.interval(now, watchers),
+        expected,
+        "{watchers} watchers"
+      );
+    }
+  }
+
+  #[test]
+  fn interval_is_clamped() {
+    let now = Utc::now();
+    let bursting = poll(
+      now - TimeDelta::days(30),
+      posts(
+        now,
+        &[
+          TimeDelta::seconds(5),
+          TimeDelta::seconds(20),
+          TimeDelta::seconds(40),
+        ],
+      ),
+    );
+    assert_eq!(bursting.interval(now, 1), *POLL_INTERVAL_MIN);
+    assert_eq!(bursting.interval(now, 1000), *POLL_INTERVAL_MIN);
+
+    let dormant = poll(
+      now - TimeDelta::days(365),
+      posts(now, &[TimeDelta::days(200)]),
+    );
+    assert_eq!(dormant.interval(now, 1), *POLL_INTERVAL_MAX);
+  }
+
+  /// Adds `count` users, due one after the other `step` apart, starting at `first`.
+  fn scheduler(count: usize, first: DateTime<Utc>, step: TimeDelta) -> Scheduler {
+    let mut scheduler = Scheduler::default();
+    for i in 0..count {
+      let at = first + step * i32::try_from(i).expect("few users");
+      scheduler.add(did(i), first, at);
+    }
+    scheduler
+  }
+
+  #[test]
+  fn users_due_close_together_are_checked_together() {
+    let now = Utc::now();
+    let window = TimeDelta::seconds(5);
+    let mut scheduler = scheduler(10, now, TimeDelta::milliseconds(300));
+
+    let due = scheduler.take_due(now, window, MAX_ACTORS);
+    // A single batch, i.e. a single getProfiles call
+    assert_eq!(due.len(), 10);
+    assert_eq!(due.chunks(MAX_ACTORS).count(), 1);
+    assert!(scheduler
+      .take_due(now + window, window, MAX_ACTORS)
+      .is_empty());
+  }
+
+  #[test]
+  fn users_are_only_taken_early_to_fill_a_batch() {
+    let now = Utc::now();
+    let window = TimeDelta::seconds(5);
+    let mut scheduler = scheduler(MAX_ACTORS + 5, now, TimeDelta::milliseconds(100));
+
+    let due = scheduler.take_due(now, window, MAX_ACTORS);
+    assert_eq!(due, (0..MAX_ACTORS).map(did).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn every_user_that_is_due_is_taken() {
+    let now = Utc::now();
+    let window = TimeDelta::seconds(5);
+    let count = MAX_ACTORS * 2 + 3;
+    let mut scheduler = scheduler(count, now - TimeDelta::seconds(60), TimeDelta::seconds(1));
+
+    // More are due than fit in a batch, none of them is left for later
+    let due = scheduler.take_due(now, window, MAX_ACTORS);
+    assert_eq!(due.len(), count);
+  }
+
+  #[test]
+  fn nothing_is_taken_before_anyone_is_due() {
+    let now = Utc::now();
+    let window = TimeDelta::seconds(5);
+    let mut scheduler = scheduler(3, now + TimeDelta::seconds(1), TimeDelta::seconds(1));
+
+    assert!(scheduler.take_due(now, window, MAX_ACTORS).is_empty());
+  }
+
+  #[test]
+  fn users_due_beyond_the_window_are_left_for_later() {
+    let now = Utc::now();
+    let window = TimeDelta::seconds(5);
+    let mut scheduler = scheduler(4, now, TimeDelta::seconds(2));
+
+    let due = scheduler.take_due(now, window, MAX_ACTORS);
+    assert_eq!(due, (0..3).map(did).collect::<Vec<_>>());
+    assert_eq!(scheduler.next_due(), Some(now + TimeDelta::seconds(6)));
+  }
 
-    #[expect(clippy::unwrap_used)] // cmp::max checked so unwrap is safe
-    let time_left = cmp::max(WATCH_DELAY * 1000 - task_delta, 0)
-      .try_into()
-      .unwrap();
-    sleep(Duration::from_millis(time_left)).await;
+  #[test]
+  fn rearm_delay_doubles_up_to_max() {
+    let max = TimeDelta::seconds(REARM_DELAY_MAX.try_into().expect("Fits in i64"));
+    assert_eq!(rearm_delay(0), *POLL_INTERVAL_MAX);
+    assert_eq!(rearm_delay(1), *POLL_INTERVAL_MAX * 2);
+    assert_eq!(rearm_delay(2), *POLL_INTERVAL_MAX * 4);
+    assert_eq!(rearm_delay(40), max.max(*POLL_INTERVAL_MAX));
   }
 }
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use environment::{IngestionMode, INGESTION_MODE};
+use repositories::watched_user;
+use tokio::time::sleep;
+use tracing::{event, Level};
+
+use crate::{health::Job, jobs::user_watcher};
+
+static RELOAD_DELAY: u64 = 60; // 60 Seconds
+
+/// Method for keeping the watchlist in sync with the database.
+///
+/// Will reload the watchlist from time to time (`RELOAD_DELAY`), so that users watched or
+/// unwatched through other instances sharing the database are picked up. When polling, users
+/// that are only now watched start being polled, while those no longer watched stop being
+/// polled on their own (check `user_watcher`). Jetstream picks up both on its own.
+pub async fn begin() {
+  let _running = Job::WatchlistReloader.running();
+  event!(Level::INFO, "Now reloading the watchlist.");
+
+  loop {
+    sleep(Duration::from_secs(RELOAD_DELAY)).await;
+
+    let newly_watched = match watched_user::reload().await {
+      Ok(newly_watched) => newly_watched,
+      Err(e) => {
+        event!(Level::WARN, "(Notice) Failed to reload the watchlist: {e}");
+        continue;
+      }
+    };
+    for watched_did in newly_watched {
+      event!(
+        Level::INFO,
+        "{watched_did} was watched through another instance."
+      );
+      if *INGESTION_MODE == IngestionMode::Polling {
+        user_watcher::watch(watched_did, Utc::now()).await;
+      }
+    }
+  }
+}
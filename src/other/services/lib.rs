@@ -1,10 +1,13 @@
 pub(crate) mod commands;
+pub mod health;
 pub mod jobs;
 pub(crate) mod notify;
 pub(crate) mod pending_messages;
+pub(crate) mod profile_changed;
 pub(crate) mod resolve_dids_and_handles;
 pub(crate) mod resolve_handles;
 pub(crate) mod unanswered_convos;
 pub(crate) mod unwatch_users;
+pub(crate) mod user_suspended;
 pub(crate) mod user_unwatched;
 pub(crate) mod watch_new_users;
@@ -30,6 +30,7 @@ pub async fn no_longer(watched_did: Did) -> Result<(), anyhow::Error> {
     convo_id,
     "(Notice) You're no longer being watched by anyone.".to_string(),
     false,
+    None,
   )
   .await?;
 
@@ -65,6 +66,7 @@ you can opt-out by blocking this bot. \
 If you have any questions, please read my bio!"
     .to_string(),
     false,
+    None,
   )
   .await?;
 
@@ -1,19 +1,54 @@
-use std::{collections::HashSet, hash::RandomState};
+use std::{
+  collections::{HashMap, HashSet},
+  hash::RandomState,
+};
 
 use atrium_api::{
   chat::bsky::convo::{defs::ConvoViewData, get_convo_for_members},
+  com::atproto::repo::strong_ref,
   types::Object,
 };
-use bsky::{get_profile, get_user_convo, send_message};
-use repositories::watched_user::{self, Watcher};
+use bsky::{get_profile, get_user_convo, send_message, FeedPost};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use repositories::{
+  notification_outbox::{self, Kind, OutboxNotification, OutboxPost},
+  watched_user::{self, Watcher},
+  watcher_settings::{self, Digest},
+};
+use tokio::sync::Notify;
 use tracing::{event, Level};
-use utils::Did;
+use utils::{post_url, Did};
+
+static EXCERPT_LENGTH: usize = 100; // 100 Characters
+static GROUPED_LIMIT: usize = 10; // 10 Posts
+static DIGEST_LIMIT: usize = 8; // 8 Watched users
+
+lazy_static! {
+  /// Notified whenever notifications are added to the outbox, so that they're delivered right away.
+  pub static ref ENQUEUED: Notify = Notify::new();
+}
 
-/// Notify the watchers of a watched user.
+/// What the watchers of a watched user are being notified about.
+#[derive(Debug, Clone)]
+pub enum Notification {
+  /// The watched user has made new posts and/or replies to someone else, from oldest to newest.
+  Posts(Vec<FeedPost>),
+  /// The watched user has opted out of being watched.
+  OptedOut,
+  /// The watched user's account is gone, i.e. deleted or unreachable for too long.
+  Gone,
+  /// The watched user's handle has changed, from `previous_handle` to their current one.
+  Renamed { previous_handle: String },
+}
+
+/// Notify the watchers of a watched user, by adding the notifications to the outbox.
+/// Posts that were already notified are skipped by the outbox, and replies are only notified
+/// to the watchers that opted in to them.
 pub async fn many(
   watched_did: Did,
   watchers: Option<HashSet<Watcher, RandomState>>,
-  is_post: bool,
+  notification: Notification,
 ) {
   event!(Level::DEBUG, "Now notifying watchers of {watched_did}.");
   let watchers = if watchers.is_some() {
@@ -24,26 +59,110 @@ pub async fn many(
 
   if let Some(watchers) = watchers {
     for u in watchers {
-      #[expect(unused_variables)] // TODO: Actually implement this feature
       let Watcher { did, watch_replies } = u;
+      let notification = match &notification {
+        Notification::Posts(posts) => {
+          let posts: Vec<_> = posts
+            .iter()
+            .filter(|post| watch_replies || !post.is_reply)
+            .cloned()
+            .collect();
+          if posts.is_empty() {
+            continue;
+          }
+          Notification::Posts(posts)
+        }
+        Notification::OptedOut => Notification::OptedOut,
+        Notification::Gone => Notification::Gone,
+        Notification::Renamed { previous_handle } => Notification::Renamed {
+          previous_handle: previous_handle.clone(),
+        },
+      };
       let watched_did = watched_did.clone();
 
       tokio::spawn(async move {
-        act(did, watched_did, is_post).await.map_err(|e| {
-          event!(Level::WARN, "(Notice) Failed to notify user: {e}");
+        enqueue(did, watched_did, notification).await.map_err(|e| {
+          event!(
+            Level::WARN,
+            "(Notice) Failed to queue notification for user: {e}"
+          );
         })
       });
     }
   }
 }
 
-/// Notify a single watcher of a watched user.
+/// Queue the notification of a single watcher of a watched user, by adding it to the outbox.
+/// If the watcher prefers digests, or is within their quiet hours, new posts are held there to
+/// be sent later in a digest. Otherwise, they're delivered right away.
+///
+/// # Errors
+/// Propagates any errors that occur during the process of contacting the database.
+async fn enqueue(
+  watcher: Did,
+  watched_did: Did,
+  notification: Notification,
+) -> Result<(), anyhow::Error> {
+  match notification {
+    Notification::Posts(posts) => {
+      let settings = watcher_settings::get(&watcher).await;
+      let held = settings.digest != Digest::Off || settings.is_quiet_at(Utc::now());
+      let posts: Vec<_> = posts
+        .into_iter()
+        .map(|post| OutboxPost {
+          uri: post.uri,
+          cid: post.cid.as_ref().to_string(),
+          text: post.text,
+          is_reply: post.is_reply,
+        })
+        .collect();
+      notification_outbox::enqueue_posts(&watcher, &watched_did, &posts, held).await?;
+      if held {
+        event!(
+          Level::DEBUG,
+          "Held {} posts from {watched_did} for {watcher}'s digest.",
+          posts.len()
+        );
+        return Ok(());
+      }
+    }
+    Notification::OptedOut => {
+      notification_outbox::enqueue_notice(&watcher, &watched_did, Kind::OptedOut).await?;
+    }
+    Notification::Gone => {
+      notification_outbox::enqueue_notice(&watcher, &watched_did, Kind::Gone).await?;
+    }
+    Notification::Renamed { previous_handle } => {
+      notification_outbox::enqueue_renamed(&watcher, &watched_did, &previous_handle).await?;
+    }
+  }
+  ENQUEUED.notify_one();
+
+  Ok(())
+}
+
+/// Deliver a batch of notifications from the outbox to a single watcher, all about the same
+/// watched user. Each new post is linked and embedded in its own message, along with an
+/// excerpt of its text, unless the watcher prefers several posts to be listed in a single
+/// message. The ids of the notifications are added to `sent` as soon as they're delivered.
 ///
 /// # Errors
-/// Propagates any errors that occur during the process of contacting the API.
-async fn act(watcher: Did, watched_did: Did, is_post: bool) -> Result<(), anyhow::Error> {
-  #[expect(clippy::unwrap_used)] // Did from job so always valid
-  let handle = get_profile::act(watched_did.parse().unwrap()).await?.handle;
+/// Propagates any errors that occur during the process of contacting the API, after which
+/// the notifications that weren't added to `sent` are yet to be delivered.
+pub async fn deliver(
+  watcher: &Did,
+  watched_did: &Did,
+  batch: &[OutboxNotification],
+  sent: &mut Vec<i64>,
+) -> Result<(), anyhow::Error> {
+  let settings = watcher_settings::get(watcher).await;
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let handle = match get_profile::act(watched_did.parse().unwrap()).await {
+    Ok(profile) => profile.handle.to_string(),
+    // Users that are gone might not have a profile anymore
+    Err(_) if batch.iter().all(|n| n.kind == Kind::Gone) => watched_did.to_string(),
+    Err(e) => return Err(e.into()),
+  };
   #[expect(clippy::unwrap_used)] // Did from DB so always valid
   let get_convo_for_members::OutputData {
     convo: Object {
@@ -53,19 +172,63 @@ async fn act(watcher: Did, watched_did: Did, is_post: bool) -> Result<(), anyhow
     ..
   } = get_user_convo::act(watcher.parse().unwrap()).await?;
 
-  let message = if is_post {
-    format!(
-      "Hey! Just wanted to let you know that @{} has posted something new. You might want to check it out!",
-      &*handle
-    )
+  let posts: Vec<_> = batch
+    .iter()
+    .filter_map(|n| n.post.as_ref().map(|post| (n.id, post)))
+    .collect();
+  if posts.len() > 1 && settings.group_posts {
+    let message = grouped_message(&handle, &posts);
+    send_message::act(convo_id.clone(), message, true, None).await?;
+    sent.extend(posts.iter().map(|(id, _)| id));
   } else {
-    format!(
-      "(Notice) @{} has opted-out of being watched... You will no longer receive notifications! Lame...",
-      &*handle
-    )
-  };
+    for (id, post) in posts {
+      let message = post_message(&handle, post);
+      let embed = post.cid.parse().ok().map(|cid| {
+        strong_ref::MainData {
+          cid,
+          uri: post.uri.clone(),
+        }
+        .into()
+      });
+      send_message::act(convo_id.clone(), message, true, embed).await?;
+      sent.push(id);
+    }
+  }
 
-  send_message::act(convo_id, message, true).await?;
+  for notice in batch.iter().filter(|n| n.post.is_none()) {
+    let message = match notice.kind {
+      Kind::OptedOut => format!(
+        "(Notice) @{} has opted-out of being watched... You will no longer receive notifications! Lame...",
+        &*handle
+      ),
+      Kind::Gone => format!(
+        "(Notice) @{}'s account seems to be gone, either deleted or unreachable for a long while... You will no longer receive notifications about them.",
+        &*handle
+      ),
+      Kind::Renamed => {
+        let Some(previous_handle) = &notice.previous_handle else {
+          event!(
+            Level::WARN,
+            "Notification {} is missing the previous handle.",
+            notice.id
+          );
+          sent.push(notice.id);
+          continue;
+        };
+        format!(
+          "(Notice) Heads up! @{previous_handle} is now @{}. You will keep receiving notifications about them as usual.",
+          &*handle
+        )
+      }
+      Kind::Post => {
+        event!(Level::WARN, "Notification {} is missing its post.", notice.id);
+        sent.push(notice.id);
+        continue;
+      }
+    };
+    send_message::act(convo_id.clone(), message, true, None).await?;
+    sent.push(notice.id);
+  }
 
   event!(
     Level::DEBUG,
@@ -74,3 +237,189 @@ async fn act(watcher: Did, watched_did: Did, is_post: bool) -> Result<(), anyhow
 
   Ok(())
 }
+
+/// Deliver a batch of notifications from the outbox that were held for a watcher's digest, as
+/// a single summarised message with one line per watched user. The ids of the notifications
+/// are added to `sent` once it's delivered.
+///
+/// # Errors
+/// Propagates any errors that occur during the process of contacting the API, after which
+/// the notifications are yet to be delivered.
+pub async fn digest(
+  watcher: &Did,
+  batch: &[OutboxNotification],
+  sent: &mut Vec<i64>,
+) -> Result<(), anyhow::Error> {
+  // Watched users, in the order they first appear in the batch
+  let mut summaries: Vec<(&Did, Vec<&OutboxPost>)> = Vec::new();
+  for (watched_did, post) in batch
+    .iter()
+    .filter_map(|n| n.post.as_ref().map(|post| (&n.watched_did, post)))
+  {
+    match summaries.iter_mut().find(|(did, _)| *did == watched_did) {
+      Some((_, posts)) => posts.push(post),
+      None => summaries.push((watched_did, vec![post])),
+    }
+  }
+
+  let mut handles = HashMap::new();
+  for (watched_did, _) in summaries.iter().take(DIGEST_LIMIT) {
+    #[expect(clippy::unwrap_used)] // Did from DB so always valid
+    let res = get_profile::act(watched_did.parse().unwrap()).await;
+    match res {
+      Ok(profile) => {
+        handles.insert(*watched_did, profile.handle.to_string());
+      }
+      // Users that are gone might not have a profile anymore
+      Err(e) => event!(
+        Level::DEBUG,
+        "Failed to get the handle of {watched_did} for {watcher}'s digest: {e}"
+      ),
+    }
+  }
+  let message = digest_message(&summaries, &handles);
+
+  #[expect(clippy::unwrap_used)] // Did from DB so always valid
+  let get_convo_for_members::OutputData {
+    convo: Object {
+      data: ConvoViewData { id: convo_id, .. },
+      ..
+    },
+    ..
+  } = get_user_convo::act(watcher.parse().unwrap()).await?;
+  send_message::act(convo_id, message, true, None).await?;
+  sent.extend(batch.iter().map(|n| n.id));
+
+  event!(Level::DEBUG, "Successfully sent digest to {watcher}.");
+
+  Ok(())
+}
+
+/// Auxiliary function to build the message for a digest, with one line per watched user.
+/// Users whose handle is unknown are referred to by their DID instead.
+fn digest_message(
+  summaries: &[(&Did, Vec<&OutboxPost>)],
+  handles: &HashMap<&Did, String>,
+) -> String {
+  let mut message = "Here's what the users you watch have been up to:\n".to_string();
+  for (watched_did, posts) in summaries.iter().take(DIGEST_LIMIT) {
+    let handle = handles
+      .get(watched_did)
+      .map_or(&***watched_did, String::as_str);
+    let replies = posts.iter().filter(|post| post.is_reply).count();
+    #[expect(clippy::unwrap_used)] // Never empty
+    let latest = post_url(&posts.last().unwrap().uri);
+    message = format!(
+      "{message}\n- @{handle}: {} new posts and {replies} replies. Latest: {latest}",
+      posts.len() - replies
+    );
+  }
+  let skipped = summaries.len().saturating_sub(DIGEST_LIMIT);
+  if skipped > 0 {
+    message = format!("{message}\n...and {skipped} other users.");
+  }
+  message
+}
+
+/// Auxiliary function to build the message for a single post.
+fn post_message(handle: &str, post: &OutboxPost) -> String {
+  let action = if post.is_reply {
+    "replied to someone"
+  } else {
+    "posted something new"
+  };
+  let mut message = format!(
+    "Hey! Just wanted to let you know that @{handle} has {action}. You might want to check it out!"
+  );
+  let excerpt = excerpt(&post.text);
+  if !excerpt.is_empty() {
+    message = format!("{message}\n\n\"{excerpt}\"");
+  }
+  format!("{message}\n\n{}", post_url(&post.uri))
+}
+
+/// Auxiliary function to build a single message listing several posts.
+/// Only the newest `GROUPED_LIMIT` posts are listed, so that the message isn't too long.
+fn grouped_message(handle: &str, posts: &[(i64, &OutboxPost)]) -> String {
+  let message = format!(
+    "Hey! Just wanted to let you know that @{handle} has posted {} new things. You might want to check them out!\n",
+    posts.len()
+  );
+  let skipped = posts.len().saturating_sub(GROUPED_LIMIT);
+  let message = posts[skipped..].iter().fold(message, |acc, (_, post)| {
+    format!("{acc}\n- {}", post_url(&post.uri))
+  });
+  if skipped > 0 {
+    format!("{message}\n...and {skipped} older ones.")
+  } else {
+    message
+  }
+}
+
+/// Auxiliary function to shorten a post's text to at most `EXCERPT_LENGTH` characters.
+fn excerpt(text: &str) -> String {
+  let text = text.trim();
+  if text.chars().count() <= EXCERPT_LENGTH {
+    return text.to_string();
+  }
+  let shortened: String = text.chars().take(EXCERPT_LENGTH - 1).collect();
+  format!("{}…", shortened.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashMap, sync::Arc};
+
+  use repositories::notification_outbox::OutboxPost;
+  use utils::Did;
+
+  use super::digest_message;
+
+  fn post(did: &Did, rkey: &str, is_reply: bool) -> OutboxPost {
+    OutboxPost {
+      uri: format!("at://{did}/app.bsky.feed.post/{rkey}"),
+      cid: String::new(),
+      text: String::new(),
+      is_reply,
+    }
+  }
+
+  #[test]
+  fn digest_lists_each_watched_user() {
+    let alice: Did = Arc::from("did:plc:alice");
+    let posts = [post(&alice, "1", false), post(&alice, "2", true)];
+    let summaries = [(&alice, posts.iter().collect())];
+    let handles = HashMap::from([(&alice, "alice.test".to_string())]);
+
+    let message = digest_message(&summaries, &handles);
+    assert!(
+      message.ends_with(
+        "\n- @alice.test: 1 new posts and 1 replies. Latest: https://bsky.app/profile/did:plc:alice/post/2"
+      ),
+      "{message}"
+    );
+  }
+
+  #[test]
+  fn digest_refers_to_users_without_a_handle_by_did() {
+    let alice: Did = Arc::from("did:plc:alice");
+    let gone: Did = Arc::from("did:plc:gone");
+    let alice_posts = [post(&alice, "1", false)];
+    let gone_posts = [post(&gone, "2", false)];
+    let summaries = [
+      (&gone, gone_posts.iter().collect()),
+      (&alice, alice_posts.iter().collect()),
+    ];
+    let handles = HashMap::from([(&alice, "alice.test".to_string())]);
+
+    let message = digest_message(&summaries, &handles);
+    assert!(
+      message.contains("\n- @did:plc:gone: 1 new posts"),
+      "{message}"
+    );
+    assert!(
+      message.contains("\n- @alice.test: 1 new posts"),
+      "{message}"
+    );
+  }
+}
@@ -1,57 +1,136 @@
-use std::collections::HashMap;
+use std::sync::Arc;
 
 use anyhow::anyhow;
-use atrium_api::chat::bsky::convo::defs::{
-  MessageViewData, MessageViewSender, MessageViewSenderData,
-};
+use atrium_api::{chat::bsky::convo::defs::MessageViewData, types::string::Did};
 use bsky::{send_message, Bsky};
+use chrono::Utc;
+use environment::owned_var_or;
 use lazy_static::lazy_static;
-use tokio::sync::RwLock;
+use repositories::command_inbox::{self, InboxCommand, Received, Status};
 use tracing::{event, Level};
 
 use crate::commands;
 
+/// How many times processing a command is attempted before giving up on it.
+static MAX_ATTEMPTS: i64 = 3;
+
 lazy_static! {
-  /// Pending messages to be processed.
-  pub static ref PENDING_MESSAGES: RwLock<HashMap<String, MessageViewData>> =
-    RwLock::new(HashMap::new());
+  /// How many commands each user may have waiting to be processed at once.
+  static ref MAX_QUEUED_COMMANDS: i64 = owned_var_or("MAX_QUEUED_COMMANDS", 5).max(1);
 }
 
-/// Adds a message to the pending messages for later processing.
-pub async fn add(convo_id: String, data: MessageViewData) {
+/// Adds a message to the command inbox for later processing.
+/// Messages that were already received before are ignored, and the ones from
+/// users with too many commands already queued are rejected with a reply.
+///
+/// # Errors
+/// Propagates any errors that occur while saving the message, in which case
+/// it must be received again later.
+pub async fn add(convo_id: String, data: MessageViewData) -> sqlx::Result<()> {
   let agent_did = Bsky::get_agent_did().await;
   if *agent_did == *data.sender.data.did {
     event!(Level::DEBUG, "Ignoring message from self.");
-    return;
+    return Ok(());
   }
 
-  PENDING_MESSAGES.write().await.insert(convo_id, data);
+  let facets = data
+    .facets
+    .map(|facets| serde_json::to_string(&facets))
+    .transpose()
+    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+  let command = InboxCommand {
+    message_id: data.id,
+    convo_id,
+    sender_did: Arc::from(data.sender.data.did.as_str()),
+    text: data.text,
+    facets,
+    sent_at: data.sent_at.as_ref().with_timezone(&Utc),
+    attempts: 0,
+  };
+  match command_inbox::receive(&command, *MAX_QUEUED_COMMANDS).await? {
+    Received::Queued => {}
+    Received::Duplicate => event!(
+      Level::DEBUG,
+      "Message {} was already received. Ignoring.",
+      command.message_id
+    ),
+    Received::Rejected => {
+      event!(
+        Level::DEBUG,
+        "User {} has too many queued commands. Rejecting message {}.",
+        command.sender_did,
+        command.message_id
+      );
+      let reply = format!(
+        "Sorry, you already have {} commands waiting to be handled, so I've ignored this one. \
+        Please wait for me to answer them, then send it again!",
+        *MAX_QUEUED_COMMANDS
+      );
+      if let Err(e) = send_message::act(command.convo_id, reply, false, None).await {
+        event!(
+          Level::WARN,
+          "(Notice) Failed to tell user about their rejected command. Error: {e}"
+        );
+      }
+    }
+  }
+  Ok(())
 }
 
-/// Processes a pending message by parsing the command then executing it.
-/// If the command is successful, it will send the message back to the user.
+/// Processes a command from the inbox by parsing it then executing it.
+/// If the command is successful, it will send the message back to the user,
+/// and the command is marked as done even if that message fails to be sent.
+/// Otherwise, it's left pending to be retried, up to `MAX_ATTEMPTS` times.
 ///
 /// # Errors
-/// Propagates any errors that occur during the process of contacting the API.
-pub async fn process(convo_id: String, data: MessageViewData) -> commands::Result<()> {
-  let MessageViewData {
-    facets,
+/// Propagates any errors that occur during the process of contacting the API
+/// or the database.
+pub async fn process(command: InboxCommand) -> commands::Result<()> {
+  let InboxCommand {
+    message_id,
+    convo_id,
+    sender_did,
     text,
-    sender: MessageViewSender {
-      data: MessageViewSenderData { did },
-      ..
-    },
+    facets,
     ..
-  } = data;
+  } = command;
 
-  event!(Level::DEBUG, "Handling message from user {}: {text}", &*did);
-  let message = commands::parse(&text, facets)
-    .await?
-    .process(did)
+  let attempts = command_inbox::start_attempt(&message_id)
     .await
     .map_err(|e| anyhow!(e))?;
+  if attempts > MAX_ATTEMPTS {
+    event!(
+      Level::WARN,
+      "(Notice) Giving up on message {message_id} after {MAX_ATTEMPTS} failed attempts."
+    );
+    return command_inbox::finish(&message_id, Status::Failed)
+      .await
+      .map_err(|e| anyhow!(e).into());
+  }
+
+  let facets = facets
+    .map(|facets| serde_json::from_str(&facets))
+    .transpose()
+    .map_err(|e| anyhow!(e))?;
+  let did = Did::new(sender_did.to_string()).map_err(|e| anyhow!(e))?;
+
+  event!(
+    Level::DEBUG,
+    "Handling message from user {sender_did}: {text}"
+  );
+  let command = commands::parse(&text, facets).await.inspect_err(|_| {
+    metrics::COMMANDS
+      .with_label_values(&["Unparsed", "error"])
+      .inc();
+  })?;
+  let name = command.name();
+  let message = command.process(did).await.map_err(|e| {
+    metrics::COMMANDS.with_label_values(&[name, "error"]).inc();
+    anyhow!(e)
+  })?;
+  metrics::COMMANDS.with_label_values(&[name, "ok"]).inc();
   drop(
-    send_message::act(convo_id, message, false)
+    send_message::act(convo_id, message, false, None)
       .await
       .map_err(|e| {
         event!(
@@ -60,5 +139,8 @@ pub async fn process(convo_id: String, data: MessageViewData) -> commands::Resul
         );
       }),
   );
+  command_inbox::finish(&message_id, Status::Done)
+    .await
+    .map_err(|e| anyhow!(e))?;
   Ok(())
 }
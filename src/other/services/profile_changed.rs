@@ -0,0 +1,72 @@
+use atrium_api::app::bsky::actor::defs::ProfileViewDetailedData;
+use bsky::get_profiles;
+use environment::TURN_OFF_HANDLE_CHANGE_NOTIFS;
+use repositories::watched_user::{self, Profile};
+use tracing::{event, Level};
+use utils::Did;
+
+use crate::notify::{self, watcher::Notification};
+
+/// The handle Bluesky reports for users whose handle couldn't be verified.
+static INVALID_HANDLE: &str = "handle.invalid";
+
+/// Takes what the bot keeps track of out of a user's profile.
+/// Handles that couldn't be verified are left out.
+#[must_use]
+pub fn from_view(profile: ProfileViewDetailedData) -> Profile {
+  let handle = profile.handle.to_string();
+  Profile {
+    handle: (handle != INVALID_HANDLE).then_some(handle),
+    display_name: profile.display_name,
+    avatar: profile.avatar,
+  }
+}
+
+/// This method handles the latest profile of a watched user, be it found while polling them or
+/// after Jetstream reported a change to their identity. So, we save it to the db and, if their
+/// handle has changed, notify their watchers.
+pub async fn handle(watched_did: Did, profile: Profile) {
+  let Some(previous) = watched_user::set_profile(&watched_did, &profile).await else {
+    return;
+  };
+  let (Some(previous_handle), Some(handle)) = (previous.handle, &profile.handle) else {
+    return;
+  };
+  if previous_handle == *handle {
+    return;
+  }
+
+  event!(
+    Level::INFO,
+    "{watched_did} has changed their handle from @{previous_handle} to @{handle}."
+  );
+  if *TURN_OFF_HANDLE_CHANGE_NOTIFS {
+    return;
+  }
+  notify::watcher::many(watched_did, None, Notification::Renamed { previous_handle }).await;
+}
+
+/// Fetches the profiles of several watched users and handles each of them, so that changes
+/// made since they were last seen are caught. Failures are logged and otherwise ignored.
+pub async fn refresh(watched_dids: &[Did]) {
+  for chunk in watched_dids.chunks(get_profiles::MAX_ACTORS) {
+    #[expect(clippy::unwrap_used)] // Did from DB so always valid
+    let actors = chunk.iter().map(|did| did.parse().unwrap()).collect();
+    let profiles = match get_profiles::act(actors).await {
+      Ok(profiles) => profiles,
+      Err(e) => {
+        event!(
+          Level::WARN,
+          "(Notice) Error fetching profiles of watched users: {e}"
+        );
+        continue;
+      }
+    };
+    for profile in profiles {
+      let Some(watched_did) = chunk.iter().find(|did| &***did == profile.did.as_str()) else {
+        continue;
+      };
+      handle(watched_did.clone(), from_view(profile)).await;
+    }
+  }
+}
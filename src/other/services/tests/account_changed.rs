@@ -0,0 +1,48 @@
+//! Drives the handling of the account status changes reported by Jetstream, which must work
+//! without the user watcher, given that it's never started along with the Jetstream listener.
+
+use std::sync::Arc;
+
+use jetstream::AccountStatus;
+use repositories::{watched_user, Database};
+use services::jobs::jetstream_listener::on_account_changed;
+use utils::Did;
+
+#[tokio::test]
+async fn account_changes_suspend_resume_and_unwatch_users() {
+  let dir = std::env::temp_dir().join(format!("account-changed-test-{}", std::process::id()));
+  std::fs::remove_dir_all(&dir).ok();
+  std::fs::create_dir_all(&dir).expect("Failed to create the test directory");
+  // Must be set before anything reads the environment
+  environment::set_overrides([(
+    "DATABASE_URL",
+    format!("sqlite://{}", dir.join("account-changed-test.db").display()),
+  )])
+  .expect("Nothing else sets the overrides");
+  Database::migrate(&sqlx::migrate!("../../app/migrations/sqlite"))
+    .await
+    .expect("Failed to migrate the test database");
+
+  let watcher: Did = Arc::from("did:plc:watcher");
+  let alice: Did = Arc::from("did:plc:alice");
+  watched_user::watch(&watcher, std::slice::from_ref(&alice), false)
+    .await
+    .expect("Failed to watch alice");
+
+  on_account_changed(
+    alice.clone(),
+    AccountStatus::Unavailable(Some("deactivated".to_string())),
+  )
+  .await;
+  assert!(watched_user::is_suspended(&alice).await);
+  assert!(watched_user::is_watched(&alice).await);
+
+  on_account_changed(alice.clone(), AccountStatus::Active).await;
+  assert!(!watched_user::is_suspended(&alice).await);
+
+  on_account_changed(alice.clone(), AccountStatus::Deleted).await;
+  assert!(!watched_user::is_watched(&alice).await);
+
+  Database::disconnect().await;
+  std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,298 @@
+//! Runs the whole flow of the bot against an in-process fake Bluesky server with scripted
+//! responses: a DM with a `!watch` command is picked up and answered, the watched user is
+//! told about it and polled, and the watcher is notified about their new post.
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use chrono::{SecondsFormat, TimeDelta, Utc};
+use repositories::Database;
+use serde_json::{json, Value};
+use services::jobs;
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::{TcpListener, TcpStream},
+  time::{sleep, timeout},
+};
+
+const BOT_USERNAME: &str = "flow-test.bot.test";
+const BOT: &str = "did:plc:bot";
+const WATCHER: &str = "did:plc:watcher";
+const ALICE: &str = "did:plc:alice";
+const ALICE_HANDLE: &str = "alice.test";
+const COMMAND: &str = "!watch @alice.test";
+const POST_TEXT: &str = "Hello from the fake server! Ping @alice.test";
+const POST_URI: &str = "at://did:plc:alice/app.bsky.feed.post/3l3qo2vuowo2b";
+const POST_CID: &str = "bafyreie5737gdxlw5i64vzichcalba3z2v5n6icifvx5xytvske7mr3hpm";
+
+/// A message the bot sent through the fake server.
+#[derive(Debug, Clone)]
+struct Sent {
+  convo_id: String,
+  text: String,
+  facets: Value,
+  embed: Value,
+}
+
+/// What the fake server has seen so far.
+#[derive(Debug, Default)]
+struct State {
+  command_read: bool,
+  sent: Vec<Sent>,
+}
+
+type Shared = Arc<Mutex<State>>;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watching_a_user_notifies_their_watcher_of_new_posts() {
+  let state = Shared::default();
+  let url = fake_server(state.clone()).await;
+
+  // The database and the session are kept apart from everything else
+  let dir = std::env::temp_dir().join(format!("flow-test-{}", std::process::id()));
+  std::fs::remove_dir_all(&dir).ok();
+  std::fs::create_dir_all(&dir).expect("Failed to create the test directory");
+  // Must be set before anything reads the environment
+  environment::set_overrides([
+    ("BSKY_SERVICE_URL", url.clone()),
+    ("BSKY_PUBLIC_API_URL", url),
+    ("BOT_USERNAME", BOT_USERNAME.to_string()),
+    ("BOT_PASSWORD", "password".to_string()),
+    ("INGESTION_MODE", "polling".to_string()),
+    (
+      "DATABASE_URL",
+      format!("sqlite://{}", dir.join("flow-test.db").display()),
+    ),
+    ("SESSION_DIR", dir.display().to_string()),
+  ])
+  .expect("Nothing else sets the overrides");
+
+  Database::migrate(&sqlx::migrate!("../../app/migrations/sqlite"))
+    .await
+    .expect("Failed to migrate the test database");
+  tokio::spawn(jobs::command_listener::begin());
+  tokio::spawn(jobs::command_issuer::begin());
+  tokio::spawn(jobs::user_watcher::begin(Utc::now()));
+  tokio::spawn(jobs::notification_sender::begin());
+
+  let sent = timeout(Duration::from_secs(30), async {
+    loop {
+      let sent = state.lock().unwrap().sent.clone();
+      if sent.iter().any(|m| m.embed.is_object()) {
+        break sent;
+      }
+      sleep(Duration::from_millis(100)).await;
+    }
+  })
+  .await;
+  Database::disconnect().await;
+  std::fs::remove_dir_all(&dir).ok();
+  let sent = sent.unwrap_or_else(|_| {
+    panic!(
+      "The watcher was never notified. Sent so far: {:#?}",
+      state.lock().unwrap().sent
+    )
+  });
+
+  let reply = sent
+    .iter()
+    .find(|m| m.convo_id == "convo-watcher" && m.text.starts_with("Now watching users:"))
+    .expect("The command was not answered");
+  assert!(reply.text.contains("@alice.test"), "{reply:?}");
+
+  let heads_up = sent
+    .iter()
+    .find(|m| m.convo_id == "convo-alice")
+    .expect("The watched user was not told about being watched");
+  assert!(heads_up.text.starts_with("Heads up!"), "{heads_up:?}");
+
+  let notification = sent
+    .iter()
+    .find(|m| m.embed.is_object())
+    .expect("Checked above");
+  assert_eq!(notification.convo_id, "convo-watcher");
+  assert!(notification
+    .text
+    .contains("@alice.test has posted something new"));
+  assert!(notification.text.contains(POST_TEXT));
+  assert_eq!(notification.embed["record"]["uri"], POST_URI);
+  // The mention in the excerpt is resolved through the public API
+  let mentions: Vec<_> = notification
+    .facets
+    .as_array()
+    .map_or_else(Vec::new, |facets| {
+      facets
+        .iter()
+        .filter_map(|f| f["features"][0]["did"].as_str())
+        .collect()
+    });
+  assert!(mentions.contains(&ALICE), "{notification:?}");
+}
+
+/// Starts the fake server on a random local port, returning its URL.
+async fn fake_server(state: Shared) -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+  tokio::spawn(async move {
+    loop {
+      let (tcp, _) = listener.accept().await.unwrap();
+      tokio::spawn(serve(tcp, state.clone()));
+    }
+  });
+  url
+}
+
+/// Reads a single HTTP request and answers it, closing the connection afterwards.
+async fn serve(mut tcp: TcpStream, state: Shared) {
+  let mut buf = Vec::new();
+  let mut chunk = [0; 4096];
+  let header_end = loop {
+    let read = tcp.read(&mut chunk).await.unwrap();
+    if read == 0 {
+      return;
+    }
+    buf.extend_from_slice(&chunk[..read]);
+    if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+      break i + 4;
+    }
+  };
+  let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+  let content_length = head
+    .lines()
+    .filter_map(|line| line.split_once(':'))
+    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+    .map_or(0, |(_, value)| value.trim().parse().unwrap());
+  while buf.len() < header_end + content_length {
+    let read = tcp.read(&mut chunk).await.unwrap();
+    if read == 0 {
+      return;
+    }
+    buf.extend_from_slice(&chunk[..read]);
+  }
+  let body = serde_json::from_slice(&buf[header_end..]).unwrap_or(Value::Null);
+
+  let target = head.split_whitespace().nth(1).unwrap_or_default();
+  let (path, query) = target.split_once('?').unwrap_or((target, ""));
+  let nsid = path.trim_start_matches("/xrpc/");
+  let (status, response) = respond(&state, nsid, query, &body).map_or_else(
+    || {
+      eprintln!("Fake server: unexpected request to {target}");
+      (
+        "501 Not Implemented",
+        json!({ "error": "MethodNotImplemented" }),
+      )
+    },
+    |response| ("200 OK", response),
+  );
+
+  let response = response.to_string();
+  let http = format!(
+    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+    response.len()
+  );
+  tcp.write_all(http.as_bytes()).await.unwrap();
+}
+
+/// The scripted responses of the fake server, by NSID.
+#[expect(clippy::significant_drop_tightening)] // The state is locked for the whole response
+fn respond(state: &Shared, nsid: &str, query: &str, body: &Value) -> Option<Value> {
+  let mut state = state.lock().unwrap();
+  let response = match nsid {
+    "com.atproto.server.createSession" => json!({
+      "accessJwt": "access",
+      "refreshJwt": "refresh",
+      "handle": BOT_USERNAME,
+      "did": BOT,
+    }),
+    "com.atproto.identity.resolveHandle" => json!({ "did": ALICE }),
+    "app.bsky.actor.getProfile" => actor(ALICE, ALICE_HANDLE),
+    "app.bsky.actor.getProfiles" => json!({ "profiles": [actor(ALICE, ALICE_HANDLE)] }),
+    "app.bsky.feed.getAuthorFeed" => json!({ "feed": [{ "post": post() }] }),
+    "chat.bsky.convo.listConvos" => json!({ "convos": [watcher_convo(!state.command_read)] }),
+    "chat.bsky.convo.updateRead" => {
+      state.command_read = true;
+      json!({ "convo": watcher_convo(false) })
+    }
+    "chat.bsky.convo.getConvoForMembers" => {
+      let convo = if query.contains("alice") {
+        convo("convo-alice", ALICE, ALICE_HANDLE)
+      } else {
+        convo("convo-watcher", WATCHER, "watcher.test")
+      };
+      json!({ "convo": convo })
+    }
+    "chat.bsky.convo.sendMessage" => {
+      let message = &body["message"];
+      let text = message["text"].as_str().unwrap_or_default().to_string();
+      state.sent.push(Sent {
+        convo_id: body["convoId"].as_str().unwrap_or_default().to_string(),
+        text: text.clone(),
+        facets: message["facets"].clone(),
+        embed: message["embed"].clone(),
+      });
+      json!({
+        "id": format!("sent-{}", state.sent.len()),
+        "rev": "1",
+        "text": text,
+        "sender": { "did": BOT },
+        "sentAt": now(TimeDelta::zero()),
+      })
+    }
+    _ => return None,
+  };
+  Some(response)
+}
+
+fn now(offset: TimeDelta) -> String {
+  (Utc::now() + offset).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn actor(did: &str, handle: &str) -> Value {
+  json!({ "did": did, "handle": handle })
+}
+
+fn convo(id: &str, did: &str, handle: &str) -> Value {
+  json!({
+    "id": id,
+    "rev": "1",
+    "members": [actor(BOT, BOT_USERNAME), actor(did, handle)],
+    "muted": false,
+    "unreadCount": 0,
+  })
+}
+
+/// The watcher's convo, whose last message is the command until it's read.
+fn watcher_convo(unread: bool) -> Value {
+  let mut convo = convo("convo-watcher", WATCHER, "watcher.test");
+  convo["unreadCount"] = json!(u8::from(unread));
+  convo["lastMessage"] = json!({
+    "$type": "chat.bsky.convo.defs#messageView",
+    "id": "command-1",
+    "rev": "1",
+    "text": COMMAND,
+    "facets": [{
+      "index": { "byteStart": 7, "byteEnd": COMMAND.len() },
+      "features": [{ "$type": "app.bsky.richtext.facet#mention", "did": ALICE }],
+    }],
+    "sender": { "did": WATCHER },
+    "sentAt": now(TimeDelta::zero()),
+  });
+  convo
+}
+
+/// A new post by the watched user, always indexed after the watch began.
+fn post() -> Value {
+  json!({
+    "uri": POST_URI,
+    "cid": POST_CID,
+    "author": actor(ALICE, ALICE_HANDLE),
+    "record": {
+      "$type": "app.bsky.feed.post",
+      "text": POST_TEXT,
+      "createdAt": now(TimeDelta::zero()),
+    },
+    "indexedAt": now(TimeDelta::seconds(1)),
+  })
+}
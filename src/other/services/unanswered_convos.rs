@@ -31,11 +31,12 @@ pub async fn handle_many(dms: Vec<ConvoViewData>) {
 
 /// Method to handle a single unanswered conversation. Tries to read the last message
 /// message initially. If that fails, it then tries to fetch all of the unread messages
-/// and handle them accordingly. Lastly, it tries to mark the conversation as read and
-/// add the message to the pending messages for later processing.
+/// and handle them accordingly, adding each message to the command inbox for later
+/// processing. Lastly, it tries to mark the conversation as read.
 ///
 /// # Errors
-/// Propagates any errors that occur during the process of contacting the API.
+/// Propagates any errors that occur during the process of contacting the API,
+/// or of saving the messages, in which case the convo is left unread.
 async fn handle(convo: ConvoViewData) -> Result<(), Error<anyhow::Error>> {
   let ConvoViewData {
     id: convo_id,
@@ -48,7 +49,9 @@ async fn handle(convo: ConvoViewData) -> Result<(), Error<anyhow::Error>> {
     match last_message.and_then(handle_union) {
       Some(ConvoViewLastMessageRefs::MessageView(view)) => {
         let Object { data, .. } = *view;
-        add(convo_id.clone(), data).await;
+        add(convo_id.clone(), data)
+          .await
+          .map_err(|e| Error::Other(anyhow!(e)))?;
       }
       Some(ConvoViewLastMessageRefs::DeletedMessageView(_)) => {
         event!(Level::DEBUG, "Message has been unsent. Ignoring.");
@@ -65,16 +68,14 @@ async fn handle(convo: ConvoViewData) -> Result<(), Error<anyhow::Error>> {
       .try_into()
       .unwrap();
     fetch_and_handle_unread(convo_id.clone(), as_non_zero).await?;
-  };
+  }
 
   // Extremely unlikely to happen, unless their API
   // starts failing in the middle of handling these.
   // Still possible, however.
   let log = "\
     (Notice) Failed to mark convo as read. \
-    Command will be added again. Possibly will be ignored by HashSet. \
-    There exists, however, a high chance that it will be handled twice. \
-    This possibly will cause the user to receive a duplicate response.";
+    Command will be received again, but ignored, since it's already in the inbox.";
 
   drop(
     read_convo::act(convo_id)
@@ -89,7 +90,8 @@ async fn handle(convo: ConvoViewData) -> Result<(), Error<anyhow::Error>> {
 /// the last message was not able to be read.
 ///
 /// # Errors
-/// Propagates any errors that occur during the process of contacting the API.
+/// Propagates any errors that occur during the process of contacting the API,
+/// or of saving the messages.
 async fn fetch_and_handle_unread(
   convo_id: String,
   unread_count: NonZeroU64,
@@ -97,11 +99,14 @@ async fn fetch_and_handle_unread(
   let unread_messages: Vec<_> = get_messages::act(convo_id.clone(), unread_count)
     .await
     .map_err(|e| anyhow!(e))?;
-  for message in unread_messages {
+  // Newest first, but the oldest must be queued first, in case there are too many
+  for message in unread_messages.into_iter().rev() {
     match message {
       OutputMessagesItem::ChatBskyConvoDefsMessageView(view) => {
         let Object { data, .. } = *view;
-        add(convo_id.clone(), data).await;
+        add(convo_id.clone(), data)
+          .await
+          .map_err(|e| Error::Other(anyhow!(e)))?;
       }
       OutputMessagesItem::ChatBskyConvoDefsDeletedMessageView(_) => {
         event!(Level::DEBUG, "Message has been unsent. Ignoring.");
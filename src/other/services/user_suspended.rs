@@ -0,0 +1,34 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use environment::owned_var_or;
+use lazy_static::lazy_static;
+use repositories::watched_user;
+use tracing::{event, Level};
+use utils::Did;
+
+use crate::user_unwatched::Reason;
+
+lazy_static! {
+  /// How long a user may stay suspended before they're deemed gone.
+  static ref MAX_SUSPENSION: TimeDelta = TimeDelta::days(owned_var_or("MAX_SUSPENSION_DAYS", 7));
+}
+
+/// This method handles the suspension of a watched user that can't be reached for now, be it
+/// because of an outage or because their account is deactivated or taken down. So, we save it
+/// to the db, unless they were already suspended, and check for how long they've been.
+///
+/// # Returns
+/// Since when the user has been suspended, or `Err(Reason::Gone)` if that's longer than
+/// `MAX_SUSPENSION_DAYS`, in which case they should no longer be watched.
+pub async fn handle(watched_did: &Did) -> Result<DateTime<Utc>, Reason> {
+  let now = Utc::now();
+  let suspended_since = watched_user::suspend(watched_did).await.unwrap_or(now);
+  if now - suspended_since >= *MAX_SUSPENSION {
+    event!(
+      Level::WARN,
+      "{watched_did} couldn't be reached for {} days, their account seems to be gone. Will stop watching.",
+      (now - suspended_since).num_days()
+    );
+    return Err(Reason::Gone);
+  }
+  Ok(suspended_since)
+}
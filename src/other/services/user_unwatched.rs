@@ -1,24 +1,55 @@
 use repositories::watched_user;
-use utils::Did;
+use tracing::{event, Level};
+use utils::{handle_api_failure, Did};
 
-use crate::notify;
+use crate::notify::{self, watcher::Notification};
 
-/// This method handles the unwatching of a user. Be it by the user blocking the bot or the bot
-/// fatally failing to check the user's posts. Users opt-out by blocking the bot. So, we delete
-/// them from the db and notify their watchers, if that is the case.
-pub async fn handle(watched_did: Did, is_block: bool) {
-  let watchers = watched_user::unwatch_all(&watched_did, is_block).await;
-  if !is_block {
+/// Why a user is no longer being watched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+  /// Users opt-out by blocking the bot.
+  OptedOut,
+  /// The user's account was deleted, or couldn't be reached for so long that it seems to be gone.
+  Gone,
+}
+
+/// This method handles the unwatching of a user. Be it by the user blocking the bot or their
+/// account being gone. So, we delete them from the db and notify their watchers.
+/// Failing to delete them is retried in incrementing intervals, and they're kept watched if
+/// it keeps failing.
+pub async fn handle(watched_did: Did, reason: Reason) {
+  let mut failures_in_a_row = 0;
+  let watchers = loop {
+    let res = watched_user::unwatch_all(&watched_did).await;
+    let e = match res {
+      Ok(watchers) => break watchers,
+      Err(e) => e,
+    };
+    event!(
+      Level::WARN,
+      "(Notice) Failed to delete watched user {watched_did} from the database: {e}"
+    );
+    if handle_api_failure(&mut failures_in_a_row).await {
+      event!(
+        Level::ERROR,
+        "Giving up on unwatching {watched_did}. They're still being watched."
+      );
+      return;
+    }
+  };
+  let Some(watchers) = watchers else {
     return;
-  }
+  };
 
-  if let Some(watchers) = watchers {
-    notify::watcher::many(watched_did.clone(), Some(watchers), false).await;
-    // No point in trying to notify the user if they've blocked the bot.
-    // tokio::spawn(async move {
-    //   notify::watched_user::no_longer(watched_did)
-    //     .await
-    //     .map_err(|e| event!(Level::WARN, "(Notice) Failed to notify user: {e}"))
-    // });
-  }
+  let notification = match reason {
+    Reason::OptedOut => Notification::OptedOut,
+    Reason::Gone => Notification::Gone,
+  };
+  notify::watcher::many(watched_did.clone(), Some(watchers), notification).await;
+  // No point in trying to notify the user if they've blocked the bot, or if they're gone.
+  // tokio::spawn(async move {
+  //   notify::watched_user::no_longer(watched_did)
+  //     .await
+  //     .map_err(|e| event!(Level::WARN, "(Notice) Failed to notify user: {e}"))
+  // });
 }
@@ -1,36 +1,52 @@
 use std::{collections::HashSet, hash::BuildHasher, sync::Arc};
 
 use atrium_api::types::string::Did;
+use chrono::Utc;
+use environment::{IngestionMode, INGESTION_MODE};
 use repositories::watched_user;
 use tracing::{event, Level};
 
 use crate::{jobs, notify};
 
 /// Method for watching new users.
-/// Will watch the user by adding the watched user (if not yet watched) and their
-/// watcher to the memory repository and database.
-/// Then, it will notify the watched user that they are being watched and start the job.
-pub async fn act<S: BuildHasher + Send>(watcher: Did, watched_users: HashSet<Did, S>) {
+/// Will watch the users by adding the watched users (if not yet watched) and their
+/// watcher to the database and memory repository, all at once. If `with_replies` is set,
+/// the watcher will also be notified about replies. Watching an already watched user
+/// updates that. Then, it will notify the newly watched users that they are being watched
+/// and, when polling, start polling them. Jetstream picks up newly watched users on its own.
+///
+/// # Errors
+///
+/// Returns an error if the changes could not be saved, in which case nothing is changed.
+pub async fn act<S: BuildHasher + Send>(
+  watcher: Did,
+  watched_users: HashSet<Did, S>,
+  with_replies: bool,
+) -> sqlx::Result<()> {
   let watcher = Arc::<str>::from(String::from(watcher));
-  for watched_did in watched_users
+  let watched_dids = watched_users
     .into_iter()
     .map(|w| Arc::<str>::from(String::from(w)))
-  {
-    // TODO: Actually implement with_replies feature: with_replies
-    if watched_user::watch(watched_did.clone(), watcher.clone(), false).await {
-      event!(Level::INFO, "Newly watched user! DID: {watched_did}");
-      tokio::spawn(jobs::user_watcher::new(watched_did.clone()));
-      tokio::spawn(async {
-        notify::watched_user::now_watched(watched_did)
-          .await
-          .map_err(|e| {
-            event!(
-              Level::WARN,
-              "(Notice) Error notifying newly watched user: {:?}",
-              e
-            );
-          })
-      });
+    .collect::<Vec<_>>();
+
+  let newly_watched = watched_user::watch(&watcher, &watched_dids, with_replies).await?;
+  for watched_did in newly_watched {
+    event!(Level::INFO, "Newly watched user! DID: {watched_did}");
+    if *INGESTION_MODE == IngestionMode::Polling {
+      jobs::user_watcher::watch(watched_did.clone(), Utc::now()).await;
     }
+    tokio::spawn(async {
+      notify::watched_user::now_watched(watched_did)
+        .await
+        .map_err(|e| {
+          event!(
+            Level::WARN,
+            "(Notice) Error notifying newly watched user: {:?}",
+            e
+          );
+        })
+    });
   }
+
+  Ok(())
 }
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// How long to wait after something failed `failures_in_a_row` times in a row: `min` at first,
+/// doubled with each failure, up to `max`.
+#[must_use]
+pub fn backoff(min: Duration, max: Duration, failures_in_a_row: u32) -> Duration {
+  2_u32
+    .checked_pow(failures_in_a_row)
+    .and_then(|factor| min.checked_mul(factor))
+    .map_or(max, |delay| delay.min(max))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::backoff;
+
+  #[test]
+  fn doubles_up_to_max() {
+    let min = Duration::from_secs(1);
+    let max = Duration::from_secs(40);
+    let delays: Vec<_> = (0..8).map(|n| backoff(min, max, n).as_secs()).collect();
+    assert_eq!(delays, [1, 2, 4, 8, 16, 32, 40, 40]);
+  }
+
+  #[test]
+  fn stays_at_max_without_overflowing() {
+    let min = Duration::from_secs(1);
+    let max = Duration::from_secs(40);
+    assert_eq!(backoff(min, max, 40), max);
+    assert_eq!(backoff(min, max, u32::MAX), max);
+  }
+}
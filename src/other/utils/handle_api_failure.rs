@@ -15,6 +15,22 @@ static MAX_FAILURES: u64 = {
   }
 };
 
+/// How long to wait before retrying after an API failure, growing by 1s with each failure
+/// in a row, up to `INCREMENTS_LIMIT`, for a maximum of `MINUTES_LIMIT` in total.
+///
+/// # Returns
+/// `None` if the maximum retries have been reached.
+#[must_use]
+pub fn api_failure_delay(failures_in_a_row: u64) -> Option<Duration> {
+  if failures_in_a_row >= MAX_FAILURES {
+    return None;
+  }
+  Some(Duration::from_secs(cmp::min(
+    failures_in_a_row + 1,
+    INCREMENTS_LIMIT,
+  )))
+}
+
 /// Handles API failures by sleeping for an incrementing amount of time.
 /// The bot will retry in incrementing intervals of 1s, up to `INCREMENTS_LIMIT`,
 /// for a maximum of `MINUTES_LIMIT`.
@@ -22,17 +38,13 @@ static MAX_FAILURES: u64 = {
 /// # Returns
 /// A bool indicating whether the maximum retries have been reached.
 pub async fn handle_api_failure(failures_in_a_row: &mut u64) -> bool {
-  if *failures_in_a_row >= MAX_FAILURES {
+  let Some(delay) = api_failure_delay(*failures_in_a_row) else {
     event!(Level::ERROR, "Maximum retries reached! Aborting...");
     return true;
-  }
+  };
 
   *failures_in_a_row += 1;
-  sleep(Duration::from_secs(cmp::min(
-    *failures_in_a_row,
-    INCREMENTS_LIMIT,
-  )))
-  .await;
+  sleep(delay).await;
 
   false
 }
@@ -0,0 +1,12 @@
+/// Utility function to tell replies apart from regular posts, given the URI of the post
+/// being replied to. Replies to the author's own posts (threads) count as regular posts.
+///
+/// # Returns
+/// `true` if the parent post was made by someone other than `author_did`.
+#[must_use]
+pub fn is_reply(author_did: &str, parent_uri: &str) -> bool {
+  let parent_author = parent_uri
+    .strip_prefix("at://")
+    .and_then(|path| path.split('/').next());
+  parent_author != Some(author_did)
+}
@@ -1,3 +1,6 @@
+mod backoff;
+pub use backoff::*;
+
 mod canonicalize_unexistent;
 use canonicalize_unexistent::canonicalize_unexistent;
 
@@ -10,5 +13,11 @@ pub use handle_api_failure::*;
 mod handle_union;
 pub use handle_union::*;
 
+mod is_reply;
+pub use is_reply::*;
+
+mod post_url;
+pub use post_url::*;
+
 use std::sync::Arc;
 pub type Did = Arc<str>;
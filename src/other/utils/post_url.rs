@@ -0,0 +1,11 @@
+/// Utility function to get the link to a post in the Bluesky web app, given its AT URI.
+#[must_use]
+pub fn post_url(uri: &str) -> String {
+  let mut parts = uri.trim_start_matches("at://").splitn(3, '/');
+  let (author, rkey) = (parts.next(), parts.nth(1));
+  format!(
+    "https://bsky.app/profile/{}/post/{}",
+    author.unwrap_or_default(),
+    rkey.unwrap_or_default()
+  )
+}